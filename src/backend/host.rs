@@ -0,0 +1,623 @@
+use alloc::{sync::Arc, vec::Vec};
+use bitflags::Flags;
+use core::{
+    mem::MaybeUninit,
+    net::{Ipv4Addr, SocketAddr},
+    sync::atomic::AtomicI32,
+    time::Duration,
+};
+use socket2::{Domain, SockAddr, Socket, Type};
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    io,
+    sync::{atomic::Ordering, Mutex, OnceLock},
+    time::Instant,
+};
+
+use crate::{
+    netc::{self, pollfd, sockaddr},
+    socket::{SocketAddrV4, ToSockaddr, ToSocketAddr},
+    types::{SocketRecvFlags, SocketSendFlags},
+    utils::NetError,
+};
+
+use super::NetBackend;
+
+/// Sockets opened through the host backend, by file descriptor
+static SOCKETS: Mutex<Option<HashMap<i32, Arc<HostSocket>>>> = Mutex::new(None);
+
+/// Next file descriptor to hand out
+static NEXT_FD: AtomicI32 = AtomicI32::new(1);
+
+/// Instant [`HostBackend::now_micros`] counts from
+static START: OnceLock<Instant> = OnceLock::new();
+
+std::thread_local! {
+    /// Error code of the last failed call of the current thread
+    static ERRNO: Cell<i32> = const { Cell::new(0) };
+}
+
+#[cfg(test)]
+std::thread_local! {
+    /// Number of upcoming sends of the current thread failing with `EAGAIN`
+    static BLOCKED_SENDS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A host socket, along with the options the host does not report back
+#[derive(Debug)]
+struct HostSocket {
+    socket: Socket,
+    nonblocking: Mutex<bool>,
+}
+
+/// The host network backend
+///
+/// It emulates the PSP network functions over the host's BSD sockets, so that
+/// the crate can run, and be tested, on a development machine. File descriptors,
+/// errno values, flags and socket options use the PSP numbering.
+///
+/// # Notes
+/// Only IPv4 is supported, like on the PSP. The access point functions always
+/// succeed, as the host is assumed to be connected.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostBackend;
+
+impl HostBackend {
+    /// Get the socket with file descriptor `fd`
+    fn get(fd: i32) -> io::Result<Arc<HostSocket>> {
+        SOCKETS
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|sockets| sockets.get(&fd).cloned())
+            .ok_or_else(|| psp_error(netc::EBADF))
+    }
+
+    /// Convert the result of a call, setting errno on failure
+    fn ret<T>(result: io::Result<T>, ok: impl FnOnce(T) -> isize) -> isize {
+        match result {
+            Ok(value) => ok(value),
+            Err(error) => {
+                ERRNO.with(|errno| errno.set(to_errno(&error)));
+                -1
+            }
+        }
+    }
+
+    /// Like [`HostBackend::ret`], for calls returning `0` on success
+    fn ret0(result: io::Result<()>) -> i32 {
+        Self::ret(result, |()| 0) as i32
+    }
+
+    /// Make the next `count` sends of the current thread fail with `EAGAIN`
+    ///
+    /// Sends over the loopback interface never block, which leaves no other
+    /// way to test how sockets handle a full send buffer.
+    #[cfg(test)]
+    pub(crate) fn block_sends(count: usize) {
+        BLOCKED_SENDS.with(|blocked| blocked.set(count));
+    }
+
+    /// Whether the current send must fail, see [`HostBackend::block_sends`]
+    fn send_blocked() -> bool {
+        #[cfg(test)]
+        if BLOCKED_SENDS.with(|blocked| blocked.replace(blocked.get().saturating_sub(1))) > 0 {
+            return true;
+        }
+        false
+    }
+}
+
+impl NetBackend for HostBackend {
+    fn socket(domain: i32, kind: i32, protocol: i32) -> i32 {
+        let result = (|| {
+            if domain != i32::from(netc::AF_INET) {
+                return Err(io::Error::from(io::ErrorKind::Unsupported));
+            }
+            let kind = match kind {
+                netc::SOCK_STREAM => Type::STREAM,
+                netc::SOCK_DGRAM => Type::DGRAM,
+                _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+            };
+            let protocol = (protocol != 0).then(|| protocol.into());
+            let socket = Socket::new(Domain::IPV4, kind, protocol)?;
+
+            let fd = NEXT_FD.fetch_add(1, Ordering::Relaxed);
+            let socket = Arc::new(HostSocket {
+                socket,
+                nonblocking: Mutex::new(false),
+            });
+            SOCKETS
+                .lock()
+                .unwrap()
+                .get_or_insert_with(HashMap::new)
+                .insert(fd, socket);
+            Ok(fd)
+        })();
+        Self::ret(result, |fd| fd as isize) as i32
+    }
+
+    fn bind(fd: i32, addr: &sockaddr) -> i32 {
+        Self::ret0(Self::get(fd).and_then(|s| s.socket.bind(&to_sock_addr(addr))))
+    }
+
+    fn listen(fd: i32, backlog: i32) -> i32 {
+        Self::ret0(Self::get(fd).and_then(|s| s.socket.listen(backlog)))
+    }
+
+    fn accept(fd: i32, addr: &mut sockaddr) -> i32 {
+        let result = Self::get(fd).and_then(|s| {
+            let (socket, peer) = s.socket.accept()?;
+            let nonblocking = *s.nonblocking.lock().unwrap();
+            socket.set_nonblocking(nonblocking)?;
+            *addr = from_sock_addr(&peer);
+
+            let fd = NEXT_FD.fetch_add(1, Ordering::Relaxed);
+            let socket = Arc::new(HostSocket {
+                socket,
+                nonblocking: Mutex::new(nonblocking),
+            });
+            SOCKETS
+                .lock()
+                .unwrap()
+                .get_or_insert_with(HashMap::new)
+                .insert(fd, socket);
+            Ok(fd)
+        });
+        Self::ret(result, |fd| fd as isize) as i32
+    }
+
+    fn connect(fd: i32, addr: &sockaddr) -> i32 {
+        let result = Self::get(fd).and_then(|s| s.socket.connect(&to_sock_addr(addr)));
+        // a non-blocking connect reports EINPROGRESS, or EWOULDBLOCK on some hosts
+        Self::ret0(result.map_err(|error| {
+            if error.kind() == io::ErrorKind::WouldBlock
+                || error.raw_os_error() == Some(libc::EINPROGRESS)
+            {
+                psp_error(netc::EINPROGRESS)
+            } else {
+                error
+            }
+        }))
+    }
+
+    fn recv(fd: i32, buf: &mut [u8], flags: i32) -> isize {
+        let buf = as_uninit(buf);
+        let result = host_flags(flags, RECV_FLAGS)
+            .and_then(|flags| Self::get(fd)?.socket.recv_with_flags(buf, flags));
+        Self::ret(result, |n| n as isize)
+    }
+
+    fn recv_from(fd: i32, buf: &mut [u8], flags: i32, from: &mut sockaddr) -> isize {
+        let buf = as_uninit(buf);
+        let result = host_flags(flags, RECV_FLAGS)
+            .and_then(|flags| Self::get(fd)?.socket.recv_from_with_flags(buf, flags));
+        Self::ret(result, |(n, addr)| {
+            *from = from_sock_addr(&addr);
+            n as isize
+        })
+    }
+
+    fn send(fd: i32, buf: &[u8], flags: i32) -> isize {
+        if Self::send_blocked() {
+            return Self::ret(Err(psp_error(netc::EAGAIN)), |()| 0);
+        }
+        let result = host_flags(flags, SEND_FLAGS)
+            .and_then(|flags| Self::get(fd)?.socket.send_with_flags(buf, flags));
+        Self::ret(result, |n| n as isize)
+    }
+
+    fn send_to(fd: i32, buf: &[u8], flags: i32, to: &sockaddr) -> isize {
+        let result = host_flags(flags, SEND_FLAGS).and_then(|flags| {
+            Self::get(fd)?
+                .socket
+                .send_to_with_flags(buf, &to_sock_addr(to), flags)
+        });
+        Self::ret(result, |n| n as isize)
+    }
+
+    fn set_option(fd: i32, level: i32, name: i32, value: &[u8]) -> i32 {
+        let result = Self::get(fd).and_then(|s| {
+            let int = read_i32(value)?;
+            let socket = &s.socket;
+            match (level, name) {
+                (netc::SOL_SOCKET, netc::SO_REUSEADDR) => socket.set_reuse_address(int != 0),
+                (netc::SOL_SOCKET, netc::SO_KEEPALIVE) => socket.set_keepalive(int != 0),
+                (netc::SOL_SOCKET, netc::SO_BROADCAST) => socket.set_broadcast(int != 0),
+                (netc::SOL_SOCKET, netc::SO_SNDBUF) => socket.set_send_buffer_size(int as usize),
+                (netc::SOL_SOCKET, netc::SO_RCVBUF) => socket.set_recv_buffer_size(int as usize),
+                (netc::SOL_SOCKET, netc::SO_SNDTIMEO) => socket.set_write_timeout(to_timeout(int)),
+                (netc::SOL_SOCKET, netc::SO_RCVTIMEO) => socket.set_read_timeout(to_timeout(int)),
+                (netc::SOL_SOCKET, netc::SO_NONBLOCK) => {
+                    socket.set_nonblocking(int != 0)?;
+                    *s.nonblocking.lock().unwrap() = int != 0;
+                    Ok(())
+                }
+                (netc::SOL_SOCKET, netc::SO_LINGER) => {
+                    let linger = read_i32(value.get(4..).unwrap_or_default())?;
+                    socket.set_linger((int != 0).then(|| Duration::from_secs(linger as u64)))
+                }
+                (netc::IPPROTO_TCP, netc::TCP_NODELAY) => socket.set_nodelay(int != 0),
+                (netc::IPPROTO_IP, netc::IP_MULTICAST_TTL) => {
+                    socket.set_multicast_ttl_v4(int as u32)
+                }
+                (netc::IPPROTO_IP, netc::IP_MULTICAST_LOOP) => {
+                    socket.set_multicast_loop_v4(int != 0)
+                }
+                (netc::IPPROTO_IP, netc::IP_ADD_MEMBERSHIP) => {
+                    let (group, interface) = read_ip_mreq(value)?;
+                    socket.join_multicast_v4(&group, &interface)
+                }
+                (netc::IPPROTO_IP, netc::IP_DROP_MEMBERSHIP) => {
+                    let (group, interface) = read_ip_mreq(value)?;
+                    socket.leave_multicast_v4(&group, &interface)
+                }
+                _ => Err(io::Error::from(io::ErrorKind::InvalidInput)),
+            }
+        });
+        Self::ret0(result)
+    }
+
+    fn get_option(fd: i32, level: i32, name: i32, value: &mut [u8]) -> i32 {
+        let result = Self::get(fd).and_then(|s| {
+            let socket = &s.socket;
+            let raw: [i32; 2] = match (level, name) {
+                (netc::SOL_SOCKET, netc::SO_REUSEADDR) => [socket.reuse_address()?.into(), 0],
+                (netc::SOL_SOCKET, netc::SO_KEEPALIVE) => [socket.keepalive()?.into(), 0],
+                (netc::SOL_SOCKET, netc::SO_BROADCAST) => [socket.broadcast()?.into(), 0],
+                (netc::SOL_SOCKET, netc::SO_SNDBUF) => [socket.send_buffer_size()? as i32, 0],
+                (netc::SOL_SOCKET, netc::SO_RCVBUF) => [socket.recv_buffer_size()? as i32, 0],
+                (netc::SOL_SOCKET, netc::SO_SNDTIMEO) => [from_timeout(socket.write_timeout()?), 0],
+                (netc::SOL_SOCKET, netc::SO_RCVTIMEO) => [from_timeout(socket.read_timeout()?), 0],
+                (netc::SOL_SOCKET, netc::SO_NONBLOCK) => {
+                    [(*s.nonblocking.lock().unwrap()).into(), 0]
+                }
+                (netc::SOL_SOCKET, netc::SO_LINGER) => match socket.linger()? {
+                    Some(linger) => [1, linger.as_secs() as i32],
+                    None => [0, 0],
+                },
+                (netc::SOL_SOCKET, netc::SO_ERROR) => {
+                    [socket.take_error()?.map_or(0, |e| to_errno(&e)), 0]
+                }
+                (netc::SOL_SOCKET, netc::SO_TYPE) => {
+                    let kind = socket.r#type()?;
+                    let kind = if kind == Type::STREAM {
+                        netc::SOCK_STREAM
+                    } else {
+                        netc::SOCK_DGRAM
+                    };
+                    [kind, 0]
+                }
+                (netc::IPPROTO_TCP, netc::TCP_NODELAY) => [socket.nodelay()?.into(), 0],
+                (netc::IPPROTO_IP, netc::IP_MULTICAST_TTL) => {
+                    [socket.multicast_ttl_v4()? as i32, 0]
+                }
+                (netc::IPPROTO_IP, netc::IP_MULTICAST_LOOP) => {
+                    [socket.multicast_loop_v4()?.into(), 0]
+                }
+                _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+            };
+            // single byte options, like BSD's `u_char` ones
+            if let [byte] = value {
+                *byte = raw[0] as u8;
+                return Ok(());
+            }
+            let bytes = [raw[0].to_ne_bytes(), raw[1].to_ne_bytes()].concat();
+            let len = value.len().min(bytes.len());
+            value[..len].copy_from_slice(&bytes[..len]);
+            Ok(())
+        });
+        Self::ret0(result)
+    }
+
+    fn local_addr(fd: i32, addr: &mut sockaddr) -> i32 {
+        let result = Self::get(fd).and_then(|s| s.socket.local_addr());
+        Self::ret(result, |local| {
+            *addr = from_sock_addr(&local);
+            0
+        }) as i32
+    }
+
+    fn peer_addr(fd: i32, addr: &mut sockaddr) -> i32 {
+        let result = Self::get(fd).and_then(|s| s.socket.peer_addr());
+        Self::ret(result, |peer| {
+            *addr = from_sock_addr(&peer);
+            0
+        }) as i32
+    }
+
+    fn shutdown(fd: i32, how: i32) -> i32 {
+        let how = match how {
+            netc::SHUT_RD => std::net::Shutdown::Read,
+            netc::SHUT_WR => std::net::Shutdown::Write,
+            netc::SHUT_RDWR => std::net::Shutdown::Both,
+            _ => return Self::ret0(Err(io::Error::from(io::ErrorKind::InvalidInput))),
+        };
+        Self::ret0(Self::get(fd).and_then(|s| s.socket.shutdown(how)))
+    }
+
+    fn close(fd: i32) -> i32 {
+        let removed = SOCKETS
+            .lock()
+            .unwrap()
+            .as_mut()
+            .and_then(|sockets| sockets.remove(&fd));
+        Self::ret0(removed.map(drop).ok_or_else(|| psp_error(netc::EBADF)))
+    }
+
+    fn poll(fds: &mut [pollfd], timeout: i32) -> i32 {
+        Self::ret(host_poll(fds, timeout), |n| n as isize) as i32
+    }
+
+    fn errno() -> i32 {
+        ERRNO.with(Cell::get)
+    }
+
+    fn now_micros() -> u64 {
+        START.get_or_init(Instant::now).elapsed().as_micros() as u64
+    }
+
+    fn delay(micros: u32) {
+        std::thread::sleep(Duration::from_micros(u64::from(micros)));
+    }
+
+    fn load_net_modules() -> Result<(), NetError> {
+        Ok(())
+    }
+
+    fn net_init() -> Result<(), NetError> {
+        START.get_or_init(Instant::now);
+        Ok(())
+    }
+
+    fn check_netconfig(_id: i32) -> i32 {
+        0
+    }
+
+    fn connect_to_access_point(_connection_id: i32) -> i32 {
+        0
+    }
+
+    fn access_point_connected() -> Result<bool, i32> {
+        Ok(true)
+    }
+}
+
+/// An error carrying a PSP errno value
+///
+/// The value is stored negated, so it cannot be confused with a host error code.
+fn psp_error(errno: i32) -> io::Error {
+    io::Error::from_raw_os_error(-errno)
+}
+
+/// Convert a host error to a PSP errno value
+fn to_errno(error: &io::Error) -> i32 {
+    match error.raw_os_error() {
+        Some(errno) if errno < 0 => return -errno,
+        #[cfg(unix)]
+        Some(libc::EMSGSIZE) => return netc::EMSGSIZE,
+        _ => {}
+    }
+    match error.kind() {
+        io::ErrorKind::WouldBlock => netc::EAGAIN,
+        io::ErrorKind::TimedOut => netc::ETIMEDOUT,
+        io::ErrorKind::ConnectionRefused => netc::ECONNREFUSED,
+        io::ErrorKind::ConnectionReset => netc::ECONNRESET,
+        io::ErrorKind::ConnectionAborted => netc::ECONNABORTED,
+        io::ErrorKind::NotConnected => netc::ENOTCONN,
+        io::ErrorKind::AddrInUse => netc::EADDRINUSE,
+        io::ErrorKind::AddrNotAvailable => netc::EADDRNOTAVAIL,
+        io::ErrorKind::HostUnreachable => netc::EHOSTUNREACH,
+        io::ErrorKind::NetworkUnreachable => netc::ENETUNREACH,
+        io::ErrorKind::BrokenPipe => netc::EPIPE,
+        io::ErrorKind::Interrupted => netc::EINTR,
+        io::ErrorKind::InvalidInput | io::ErrorKind::Unsupported => netc::EINVAL,
+        io::ErrorKind::OutOfMemory => netc::ENOMEM,
+        _ => netc::EIO,
+    }
+}
+
+/// Send flags, with their PSP and host values
+#[cfg(unix)]
+const SEND_FLAGS: &[(SocketSendFlags, i32)] = &[
+    (SocketSendFlags::MSG_OOB, libc::MSG_OOB),
+    (SocketSendFlags::MSG_EOR, libc::MSG_EOR),
+    (SocketSendFlags::MSG_DONTWAIT, libc::MSG_DONTWAIT),
+];
+
+/// Receive flags, with their PSP and host values
+#[cfg(unix)]
+const RECV_FLAGS: &[(SocketRecvFlags, i32)] = &[
+    (SocketRecvFlags::MSG_OOB, libc::MSG_OOB),
+    (SocketRecvFlags::MSG_PEEK, libc::MSG_PEEK),
+    (SocketRecvFlags::MSG_WAITALL, libc::MSG_WAITALL),
+    (SocketRecvFlags::MSG_DONTWAIT, libc::MSG_DONTWAIT),
+];
+
+/// Send flags, with their PSP and host values
+///
+/// None of them is supported on this host.
+#[cfg(not(unix))]
+const SEND_FLAGS: &[(SocketSendFlags, i32)] = &[];
+
+/// Receive flags, with their PSP and host values
+///
+/// None of them is supported on this host.
+#[cfg(not(unix))]
+const RECV_FLAGS: &[(SocketRecvFlags, i32)] = &[];
+
+/// Convert PSP send or receive flags to the host values in `table`
+///
+/// Flags without a host counterpart are rejected rather than dropped.
+fn host_flags<F: Flags<Bits = u32> + Copy>(flags: i32, table: &[(F, i32)]) -> io::Result<i32> {
+    let mut unknown = flags as u32;
+    let mut converted = 0;
+    for &(psp, host) in table {
+        if unknown & psp.bits() != 0 {
+            unknown &= !psp.bits();
+            converted |= host;
+        }
+    }
+    if unknown == 0 {
+        Ok(converted)
+    } else {
+        Err(psp_error(netc::EINVAL))
+    }
+}
+
+/// Poll events, with their PSP and host values
+#[cfg(unix)]
+const POLL_EVENTS: [(i16, i16); 6] = [
+    (netc::POLLIN, libc::POLLIN),
+    (netc::POLLPRI, libc::POLLPRI),
+    (netc::POLLOUT, libc::POLLOUT),
+    (netc::POLLERR, libc::POLLERR),
+    (netc::POLLHUP, libc::POLLHUP),
+    (netc::POLLNVAL, libc::POLLNVAL),
+];
+
+/// Poll PSP sockets with the host's `poll`
+///
+/// Unknown file descriptors are reported with `POLLNVAL`, like the PSP does.
+#[cfg(unix)]
+fn host_poll(fds: &mut [pollfd], timeout: i32) -> io::Result<usize> {
+    use std::os::fd::AsRawFd;
+
+    let sockets: Vec<_> = fds.iter().map(|fd| HostBackend::get(fd.fd).ok()).collect();
+    let mut host_fds: Vec<libc::pollfd> = fds
+        .iter()
+        .zip(&sockets)
+        .map(|(fd, socket)| libc::pollfd {
+            // negative file descriptors are ignored
+            fd: socket.as_ref().map_or(-1, |s| s.socket.as_raw_fd()),
+            events: convert_events(fd.events, |(psp, host)| (psp, host)),
+            revents: 0,
+        })
+        .collect();
+    // do not wait for the other sockets when one is invalid
+    let timeout = if sockets.iter().any(Option::is_none) {
+        0
+    } else {
+        timeout
+    };
+
+    let result = unsafe {
+        libc::poll(
+            host_fds.as_mut_ptr(),
+            host_fds.len() as libc::nfds_t,
+            timeout,
+        )
+    };
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut ready = 0;
+    for ((fd, host_fd), socket) in fds.iter_mut().zip(&host_fds).zip(&sockets) {
+        fd.revents = match socket {
+            Some(_) => convert_events(host_fd.revents, |(psp, host)| (host, psp)),
+            None => netc::POLLNVAL,
+        };
+        if fd.revents != 0 {
+            ready += 1;
+        }
+    }
+    Ok(ready)
+}
+
+/// Poll PSP sockets with the host's `poll`
+///
+/// Not supported on this host.
+#[cfg(not(unix))]
+fn host_poll(_fds: &mut [pollfd], _timeout: i32) -> io::Result<usize> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Convert poll events between PSP and host values
+///
+/// `direction` maps a pair of [`POLL_EVENTS`] to the (from, to) values.
+#[cfg(unix)]
+fn convert_events(events: i16, direction: impl Fn((i16, i16)) -> (i16, i16)) -> i16 {
+    POLL_EVENTS
+        .into_iter()
+        .map(direction)
+        .filter(|(from, _)| events & from != 0)
+        .fold(0, |converted, (_, to)| converted | to)
+}
+
+/// Convert a PSP [`sockaddr`] to a host address
+fn to_sock_addr(addr: &sockaddr) -> SockAddr {
+    if addr.sa_family == netc::AF_UNSPEC {
+        return unspecified_sock_addr();
+    }
+    SockAddr::from(addr.to_socket_addr())
+}
+
+/// A host address of the unspecified family
+#[cfg(unix)]
+fn unspecified_sock_addr() -> SockAddr {
+    // SAFETY: a zeroed storage is a valid address of family `AF_UNSPEC`
+    unsafe {
+        let storage: libc::sockaddr_storage = core::mem::zeroed();
+        SockAddr::new(storage, size_of::<libc::sockaddr>() as libc::socklen_t)
+    }
+}
+
+/// A host address of the unspecified family
+///
+/// Not supported on this host: the unspecified IPv4 address is used instead.
+#[cfg(not(unix))]
+fn unspecified_sock_addr() -> SockAddr {
+    SockAddr::from(SocketAddr::from(([0, 0, 0, 0], 0)))
+}
+
+/// Convert a host address to a PSP [`sockaddr`]
+fn from_sock_addr(addr: &SockAddr) -> sockaddr {
+    match addr.as_socket() {
+        Some(SocketAddr::V4(addr)) => addr.to_sockaddr(),
+        _ => SocketAddrV4::new(core::net::Ipv4Addr::UNSPECIFIED, 0).to_sockaddr(),
+    }
+}
+
+/// Read the first `i32` of an option value, or its only byte for single byte options
+fn read_i32(value: &[u8]) -> io::Result<i32> {
+    if let [byte] = value {
+        return Ok(i32::from(*byte));
+    }
+    value
+        .get(..4)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(i32::from_ne_bytes)
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))
+}
+
+/// Read a raw `ip_mreq` option value: the group and interface addresses
+fn read_ip_mreq(value: &[u8]) -> io::Result<(Ipv4Addr, Ipv4Addr)> {
+    match value {
+        [g0, g1, g2, g3, i0, i1, i2, i3] => Ok((
+            Ipv4Addr::new(*g0, *g1, *g2, *g3),
+            Ipv4Addr::new(*i0, *i1, *i2, *i3),
+        )),
+        _ => Err(io::Error::from(io::ErrorKind::InvalidInput)),
+    }
+}
+
+/// Convert a raw timeout, in microseconds, to a host timeout
+fn to_timeout(raw: i32) -> Option<Duration> {
+    (raw != 0).then(|| Duration::from_micros(u64::from(raw as u32)))
+}
+
+/// Convert a host timeout to a raw timeout, in microseconds
+fn from_timeout(timeout: Option<Duration>) -> i32 {
+    timeout.map_or(0, |timeout| {
+        u32::try_from(timeout.as_micros()).unwrap_or(u32::MAX) as i32
+    })
+}
+
+/// View an initialized buffer as a possibly uninitialized one
+fn as_uninit(buf: &mut [u8]) -> &mut [MaybeUninit<u8>] {
+    // SAFETY: `MaybeUninit<u8>` has the same layout as `u8`, and the host only
+    // writes initialized bytes to the buffer
+    unsafe { &mut *(core::ptr::from_mut::<[u8]>(buf) as *mut [MaybeUninit<u8>]) }
+}
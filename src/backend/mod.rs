@@ -0,0 +1,127 @@
+//! Network backends
+//!
+//! All the system calls made by the crate's sockets, DNS resolver and utilities
+//! go through a type implementing [`NetBackend`]. Two backends are provided:
+//! - [`PspBackend`] (feature `psp`): calls the PSP `sceNetInet*` functions.
+//! - [`HostBackend`] (feature `host`): emulates them over the host sockets, so the
+//!   crate's networking code can run, and be tested, on a development machine.
+//!
+//! The backend in use is [`DefaultBackend`]. The host backend requires `std`,
+//! which cannot be linked together with the `psp` crate: it is therefore only
+//! available when the `psp` feature is disabled.
+//!
+//! The functions mirror the PSP ones: they return a negative value on failure,
+//! in which case the error code can be retrieved with [`NetBackend::errno`].
+
+#[cfg(all(feature = "host", not(feature = "psp")))]
+mod host;
+#[cfg(feature = "psp")]
+mod psp;
+
+#[cfg(all(feature = "host", not(feature = "psp")))]
+pub use self::host::HostBackend;
+#[cfg(feature = "psp")]
+pub use self::psp::PspBackend;
+
+use crate::{
+    netc::{pollfd, sockaddr},
+    utils::NetError,
+};
+
+/// The backend used by the crate
+#[cfg(feature = "psp")]
+pub type DefaultBackend = PspBackend;
+/// The backend used by the crate
+#[cfg(not(feature = "psp"))]
+pub type DefaultBackend = HostBackend;
+
+/// The network system calls used by the crate
+pub trait NetBackend {
+    /// Create a socket, returning its file descriptor
+    fn socket(domain: i32, kind: i32, protocol: i32) -> i32;
+
+    /// Bind a socket to a local address
+    fn bind(fd: i32, addr: &sockaddr) -> i32;
+
+    /// Listen for incoming connections
+    fn listen(fd: i32, backlog: i32) -> i32;
+
+    /// Accept an incoming connection, returning the new socket's file descriptor.
+    /// The address of the peer is stored in `addr`.
+    fn accept(fd: i32, addr: &mut sockaddr) -> i32;
+
+    /// Connect a socket to a remote address
+    fn connect(fd: i32, addr: &sockaddr) -> i32;
+
+    /// Receive data, returning the number of bytes received
+    fn recv(fd: i32, buf: &mut [u8], flags: i32) -> isize;
+
+    /// Receive data, returning the number of bytes received.
+    /// The address of the sender is stored in `from`.
+    fn recv_from(fd: i32, buf: &mut [u8], flags: i32, from: &mut sockaddr) -> isize;
+
+    /// Send data, returning the number of bytes sent
+    fn send(fd: i32, buf: &[u8], flags: i32) -> isize;
+
+    /// Send data to `to`, returning the number of bytes sent
+    fn send_to(fd: i32, buf: &[u8], flags: i32, to: &sockaddr) -> isize;
+
+    /// Set a socket option, whose raw value is `value`
+    fn set_option(fd: i32, level: i32, name: i32, value: &[u8]) -> i32;
+
+    /// Get a socket option, storing its raw value in `value`
+    fn get_option(fd: i32, level: i32, name: i32, value: &mut [u8]) -> i32;
+
+    /// Get the local address of a socket
+    fn local_addr(fd: i32, addr: &mut sockaddr) -> i32;
+
+    /// Get the address of the peer a socket is connected to
+    fn peer_addr(fd: i32, addr: &mut sockaddr) -> i32;
+
+    /// Shut down part of a full-duplex connection
+    fn shutdown(fd: i32, how: i32) -> i32;
+
+    /// Close a socket
+    fn close(fd: i32) -> i32;
+
+    /// Wait until at least one socket of `fds` is ready, or `timeout`
+    /// milliseconds elapsed, like BSD's `poll`
+    ///
+    /// The events that occurred are stored in the `revents` of each entry. A
+    /// negative timeout waits indefinitely. Returns the number of entries with
+    /// events, which is `0` if the timeout expired.
+    fn poll(fds: &mut [pollfd], timeout: i32) -> i32;
+
+    /// The error code of the last failed call of the current thread
+    fn errno() -> i32;
+
+    /// Current time, in microseconds
+    fn now_micros() -> u64;
+
+    /// Suspend the current thread for `micros` microseconds
+    fn delay(micros: u32);
+
+    /// Load the network modules
+    ///
+    /// # Errors
+    /// - [`NetError::LoadModuleFailed`] if a module could not be loaded
+    fn load_net_modules() -> Result<(), NetError>;
+
+    /// Initialize the network stack
+    ///
+    /// # Errors
+    /// - [`NetError::InitFailed`] if the stack could not be initialized
+    fn net_init() -> Result<(), NetError>;
+
+    /// Check the existence of a network configuration, returning `0` if it exists
+    fn check_netconfig(id: i32) -> i32;
+
+    /// Start connecting to an access point
+    fn connect_to_access_point(connection_id: i32) -> i32;
+
+    /// Whether the connection to the access point is established
+    ///
+    /// # Errors
+    /// The error code of the failed call
+    fn access_point_connected() -> Result<bool, i32>;
+}
@@ -0,0 +1,271 @@
+use ::psp::sys;
+use core::ffi::c_void;
+
+use crate::{
+    netc::{self, pollfd, sockaddr},
+    utils::NetError,
+};
+
+use super::NetBackend;
+
+/// Length of a [`sockaddr`]
+const SOCKADDR_LEN: u32 = core::mem::size_of::<sockaddr>() as u32;
+
+/// The PSP network backend
+///
+/// It calls the PSP `sceNetInet*` functions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PspBackend;
+
+impl NetBackend for PspBackend {
+    fn socket(domain: i32, kind: i32, protocol: i32) -> i32 {
+        unsafe { sys::sceNetInetSocket(domain, kind, protocol) }
+    }
+
+    fn bind(fd: i32, addr: &sockaddr) -> i32 {
+        unsafe { sys::sceNetInetBind(fd, addr, SOCKADDR_LEN) }
+    }
+
+    fn listen(fd: i32, backlog: i32) -> i32 {
+        unsafe { sys::sceNetInetListen(fd, backlog) }
+    }
+
+    fn accept(fd: i32, addr: &mut sockaddr) -> i32 {
+        let mut len = SOCKADDR_LEN;
+        unsafe { sys::sceNetInetAccept(fd, addr, &raw mut len) }
+    }
+
+    fn connect(fd: i32, addr: &sockaddr) -> i32 {
+        let result = unsafe { sys::sceNetInetConnect(fd, addr, SOCKADDR_LEN) };
+        // like other BSD-derived stacks, the PSP dissolves the association of
+        // a datagram socket connected to an `AF_UNSPEC` address, but reports
+        // EAFNOSUPPORT
+        if result < 0 && addr.sa_family == netc::AF_UNSPEC && Self::errno() == netc::EAFNOSUPPORT {
+            0
+        } else {
+            result
+        }
+    }
+
+    fn recv(fd: i32, buf: &mut [u8], flags: i32) -> isize {
+        unsafe { sys::sceNetInetRecv(fd, buf.as_mut_ptr().cast::<c_void>(), buf.len(), flags) }
+    }
+
+    fn recv_from(fd: i32, buf: &mut [u8], flags: i32, from: &mut sockaddr) -> isize {
+        let mut len = SOCKADDR_LEN;
+        unsafe {
+            sys::sceNetInetRecvfrom(
+                fd,
+                buf.as_mut_ptr().cast::<c_void>(),
+                buf.len(),
+                flags,
+                from,
+                &raw mut len,
+            )
+        }
+    }
+
+    fn send(fd: i32, buf: &[u8], flags: i32) -> isize {
+        unsafe { sys::sceNetInetSend(fd, buf.as_ptr().cast::<c_void>(), buf.len(), flags) }
+    }
+
+    fn send_to(fd: i32, buf: &[u8], flags: i32, to: &sockaddr) -> isize {
+        unsafe {
+            sys::sceNetInetSendto(
+                fd,
+                buf.as_ptr().cast::<c_void>(),
+                buf.len(),
+                flags,
+                to,
+                SOCKADDR_LEN,
+            )
+        }
+    }
+
+    fn set_option(fd: i32, level: i32, name: i32, value: &[u8]) -> i32 {
+        unsafe {
+            sys::sceNetInetSetsockopt(
+                fd,
+                level,
+                name,
+                value.as_ptr().cast::<c_void>(),
+                value.len() as u32,
+            )
+        }
+    }
+
+    fn get_option(fd: i32, level: i32, name: i32, value: &mut [u8]) -> i32 {
+        let mut len = value.len() as u32;
+        unsafe {
+            sys::sceNetInetGetsockopt(
+                fd,
+                level,
+                name,
+                value.as_mut_ptr().cast::<c_void>(),
+                &raw mut len,
+            )
+        }
+    }
+
+    fn local_addr(fd: i32, addr: &mut sockaddr) -> i32 {
+        let mut len = SOCKADDR_LEN;
+        unsafe { sys::sceNetInetGetsockname(fd, addr, &raw mut len) }
+    }
+
+    fn peer_addr(fd: i32, addr: &mut sockaddr) -> i32 {
+        let mut len = SOCKADDR_LEN;
+        unsafe { sys::sceNetInetGetpeername(fd, addr, &raw mut len) }
+    }
+
+    fn shutdown(fd: i32, how: i32) -> i32 {
+        unsafe { sys::sceNetInetShutdown(fd, how) }
+    }
+
+    fn close(fd: i32) -> i32 {
+        unsafe { sys::sceNetInetClose(fd) }
+    }
+
+    fn poll(fds: &mut [pollfd], timeout: i32) -> i32 {
+        unsafe { inet::sceNetInetPoll(fds.as_mut_ptr(), fds.len() as u32, timeout) }
+    }
+
+    fn errno() -> i32 {
+        unsafe { sys::sceNetInetGetErrno() }
+    }
+
+    fn now_micros() -> u64 {
+        let mut tick = 0;
+        let resolution = unsafe {
+            sys::sceRtcGetCurrentTick(&raw mut tick);
+            sys::sceRtcGetTickResolution()
+        };
+        (u128::from(tick) * 1_000_000 / u128::from(resolution.max(1))) as u64
+    }
+
+    fn delay(micros: u32) {
+        unsafe { sys::sceKernelDelayThread(micros) };
+    }
+
+    fn load_net_modules() -> Result<(), NetError> {
+        unsafe {
+            let res = sys::sceUtilityLoadNetModule(sys::NetModule::NetCommon);
+            if res != 0 {
+                return Err(NetError::load_module_failed("", res));
+            }
+
+            let res = sys::sceUtilityLoadNetModule(sys::NetModule::NetInet);
+            if res != 0 {
+                return Err(NetError::load_module_failed("", res));
+            }
+
+            let res = sys::sceUtilityLoadNetModule(sys::NetModule::NetParseUri);
+            if res != 0 {
+                return Err(NetError::load_module_failed("", res));
+            }
+
+            let res = sys::sceUtilityLoadNetModule(sys::NetModule::NetHttp);
+            if res != 0 {
+                return Err(NetError::load_module_failed("", res));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn net_init() -> Result<(), NetError> {
+        unsafe {
+            let res = sys::sceNetInit(0x20000, 0x20, 0x1000, 0x20, 0x1000);
+            if res != 0 {
+                return Err(NetError::init_failed("sceNetInit", res));
+            }
+
+            let res = sys::sceNetInetInit();
+            if res != 0 {
+                return Err(NetError::init_failed("sceNetInetInit", res));
+            }
+
+            let res = sys::sceNetResolverInit();
+            if res != 0 {
+                return Err(NetError::init_failed("sceNetResolverInit", res));
+            }
+
+            let res = sys::sceNetApctlInit(0x1600, 42);
+            if res != 0 {
+                return Err(NetError::init_failed("sceNetApctlInit", res));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_netconfig(id: i32) -> i32 {
+        unsafe { sys::sceUtilityCheckNetParam(id) }
+    }
+
+    fn connect_to_access_point(connection_id: i32) -> i32 {
+        unsafe { sys::sceNetApctlConnect(connection_id) }
+    }
+
+    fn access_point_connected() -> Result<bool, i32> {
+        let mut state: sys::ApctlState = unsafe { core::mem::zeroed() };
+        let err = unsafe { sys::sceNetApctlGetState(&raw mut state) };
+        if err != 0 {
+            return Err(err);
+        }
+        Ok(matches!(state, sys::ApctlState::GotIp))
+    }
+}
+
+/// Functions of the `sceNetInet` library the `psp` crate does not bind
+///
+/// Their NIDs and stubs go in the sections of the library the `psp` crate
+/// declares: the linker sorts them along with the others, and the loader
+/// patches the stubs like those of the bound functions.
+#[allow(non_upper_case_globals, non_snake_case)]
+mod inet {
+    use crate::netc::pollfd;
+
+    /// A function stub, replaced by a system call by the loader
+    ///
+    /// Like the stubs of the `psp` crate, it references the NID, so that the
+    /// NID is only linked in if the function is called.
+    #[cfg(target_os = "psp")]
+    #[repr(C)]
+    struct Stub {
+        // never read: overwritten by the loader
+        #[allow(dead_code)]
+        lib_addr: &'static u32,
+        #[allow(dead_code)]
+        nid_addr: &'static u32,
+    }
+
+    #[cfg(target_os = "psp")]
+    #[link_section = ".rodata.sceNid.sceNetInet.sceNetInetPoll"]
+    static __sceNetInetPoll_NID: u32 = 0xFAAB_B1DD;
+
+    #[cfg(target_os = "psp")]
+    #[link_section = ".sceStub.text.sceNetInet.sceNetInetPoll"]
+    #[no_mangle]
+    static __sceNetInetPoll_stub: Stub = Stub {
+        lib_addr: &__sceNetInetPoll_NID,
+        nid_addr: &__sceNetInetPoll_NID,
+    };
+
+    /// Wait until at least one socket of `fds` is ready, like BSD's `poll`
+    pub(super) unsafe fn sceNetInetPoll(fds: *mut pollfd, nfds: u32, timeout: i32) -> i32 {
+        #[cfg(target_os = "psp")]
+        {
+            extern "C" {
+                #[link_name = "__sceNetInetPoll_stub"]
+                fn stub(fds: *mut pollfd, nfds: u32, timeout: i32) -> i32;
+            }
+            stub(fds, nfds, timeout)
+        }
+
+        #[cfg(not(target_os = "psp"))]
+        {
+            let _ = (fds, nfds, timeout);
+            panic!("tried to call PSP system function on non-PSP target");
+        }
+    }
+}
@@ -1,24 +1,45 @@
 #![allow(clippy::module_name_repetitions)]
 
+use crate::netc::in_addr;
 use alloc::{
     borrow::ToOwned,
+    format,
     string::{String, ToString},
     vec as a_vec,
+    vec::Vec,
 };
-use core::net::{IpAddr, Ipv4Addr, SocketAddr};
-use dns_protocol::{Flags, Question, ResourceRecord};
-use embedded_io::{Read, Write};
-use psp::sys::in_addr;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::time::Duration;
+use dns_protocol::{Flags, Question, ResourceRecord, ResourceType};
 use thiserror::Error;
 
-use crate::socket::state::Connected;
+use crate::{
+    backend::{DefaultBackend, NetBackend},
+    socket::{
+        poll::{self, Interest, Pollable},
+        state::{Bound, Connected},
+    },
+};
 
 use super::{
-    socket::{udp::UdpSocket, ToSocketAddr},
+    socket::{tcp::TcpSocket, udp::UdpSocket, ToSocketAddr},
     traits,
 };
 
+mod cache;
+pub use cache::{CachingResolver, DEFAULT_CACHE_CAPACITY, DEFAULT_NEGATIVE_TTL, DEFAULT_TTL};
+
 pub const DNS_PORT: u16 = 53;
+
+/// Default time a [`DnsResolver`] waits for the answer of a server, per attempt
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default number of times a [`DnsResolver`] queries the servers again after
+/// they all failed
+pub const DEFAULT_RETRIES: usize = 2;
+
+/// Default time a [`DnsResolver`] waits before querying the servers again
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(250);
 lazy_static::lazy_static! {
     static ref GOOGLE_DNS_HOST: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(8, 8, 8, 8)), DNS_PORT);
 }
@@ -30,26 +51,262 @@ pub fn create_a_type_query(domain: &str) -> Question {
     Question::new(domain, dns_protocol::ResourceType::A, 1)
 }
 
+/// Create a DNS query for the PTR record of an IPv4 address
+#[must_use]
+pub fn create_ptr_type_query(name: &str) -> Question<'_> {
+    Question::new(name, ResourceType::Ptr, 1)
+}
+
+/// Create a DNS query for the records of type `ty` of a domain
+#[must_use]
+pub fn create_query(domain: &str, ty: ResourceType) -> Question<'_> {
+    Question::new(domain, ty, 1)
+}
+
+/// The domain name under which the PTR record of `addr` is published
+///
+/// # Example
+/// ```
+/// use psp_net::{dns::reverse_name, netc::in_addr};
+///
+/// let addr = in_addr(u32::from_be_bytes([192, 168, 1, 42]));
+/// assert_eq!(reverse_name(&addr), "42.1.168.192.in-addr.arpa");
+/// ```
+#[must_use]
+pub fn reverse_name(addr: &in_addr) -> String {
+    let [a, b, c, d] = addr.0.to_be_bytes();
+    format!("{d}.{c}.{b}.{a}.in-addr.arpa")
+}
+
+/// Maximum number of compression pointers followed when decoding a name
+const MAX_NAME_POINTERS: usize = 16;
+
+/// Decode the, possibly compressed, domain name at `offset` in `message`
+///
+/// # Returns
+/// The name, with its labels separated by dots, or `None` if it is malformed
+fn decode_name(message: &[u8], mut offset: usize) -> Option<String> {
+    let mut name = String::new();
+    let mut pointers = 0;
+    loop {
+        let len = *message.get(offset)?;
+        match len & 0xc0 {
+            0x00 if len == 0 => return Some(name),
+            0x00 => {
+                let label = message.get(offset + 1..offset + 1 + usize::from(len))?;
+                if !name.is_empty() {
+                    name.push('.');
+                }
+                name.push_str(core::str::from_utf8(label).ok()?);
+                offset += 1 + usize::from(len);
+            }
+            0xc0 => {
+                pointers += 1;
+                if pointers > MAX_NAME_POINTERS {
+                    return None;
+                }
+                let low = *message.get(offset + 1)?;
+                offset = usize::from(u16::from_be_bytes([len & 0x3f, low]));
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Offset of `data` in `message`, which must contain it
+fn offset_in(message: &[u8], data: &[u8]) -> usize {
+    data.as_ptr() as usize - message.as_ptr() as usize
+}
+
+/// Suspend the current thread for `duration`
+fn delay(duration: Duration) {
+    DefaultBackend::delay(u32::try_from(duration.as_micros()).unwrap_or(u32::MAX));
+}
+
+/// Maximum number of CNAME records followed to get to the records of a name
+const MAX_CNAME_HOPS: usize = 8;
+
+/// A DNS resource record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DnsRecord {
+    /// The IPv4 address of the domain
+    A(Ipv4Addr),
+    /// The IPv6 address of the domain
+    Aaaa(Ipv6Addr),
+    /// The canonical name the domain is an alias of
+    Cname(String),
+    /// The domain name of an address
+    Ptr(String),
+    /// A mail exchange of the domain
+    Mx {
+        /// The preference of the exchange, the lowest being preferred
+        preference: u16,
+        /// The domain name of the exchange
+        exchange: String,
+    },
+    /// The character strings of a text record
+    ///
+    /// Strings that are not UTF-8 are converted lossily.
+    Txt(Vec<String>),
+    /// The location of a service
+    Srv {
+        /// The priority of the target, the lowest being contacted first
+        priority: u16,
+        /// The relative weight of targets with the same priority
+        weight: u16,
+        /// The port of the service on the target
+        port: u16,
+        /// The domain name of the target
+        target: String,
+    },
+}
+
+impl DnsRecord {
+    /// The type of the record
+    #[must_use]
+    pub fn resource_type(&self) -> ResourceType {
+        match self {
+            DnsRecord::A(_) => ResourceType::A,
+            DnsRecord::Aaaa(_) => ResourceType::AAAA,
+            DnsRecord::Cname(_) => ResourceType::CName,
+            DnsRecord::Ptr(_) => ResourceType::Ptr,
+            DnsRecord::Mx { .. } => ResourceType::MX,
+            DnsRecord::Txt(_) => ResourceType::Txt,
+            DnsRecord::Srv { .. } => ResourceType::Srv,
+        }
+    }
+
+    /// Parse a resource record of `message`
+    ///
+    /// # Returns
+    /// The record, or `None` if its type is not supported or it is malformed
+    fn parse(message: &[u8], record: &ResourceRecord<'_>) -> Option<Self> {
+        let data = record.data();
+        // names in the data may point to names elsewhere in the message
+        let name_at = |start: usize| decode_name(message, offset_in(message, data) + start);
+        let u16_at = |start: usize| {
+            Some(u16::from_be_bytes(
+                data.get(start..start + 2)?.try_into().ok()?,
+            ))
+        };
+
+        match record.ty() {
+            ResourceType::A => Some(DnsRecord::A(Ipv4Addr::from(
+                <[u8; 4]>::try_from(data).ok()?,
+            ))),
+            ResourceType::AAAA => Some(DnsRecord::Aaaa(Ipv6Addr::from(
+                <[u8; 16]>::try_from(data).ok()?,
+            ))),
+            ResourceType::CName => name_at(0).map(DnsRecord::Cname),
+            ResourceType::Ptr => name_at(0).map(DnsRecord::Ptr),
+            ResourceType::MX => Some(DnsRecord::Mx {
+                preference: u16_at(0)?,
+                exchange: name_at(2)?,
+            }),
+            ResourceType::Txt => {
+                let mut strings = Vec::new();
+                let mut rest = data;
+                while let Some((&len, tail)) = rest.split_first() {
+                    let string = tail.get(..usize::from(len))?;
+                    strings.push(String::from_utf8_lossy(string).into_owned());
+                    rest = &tail[usize::from(len)..];
+                }
+                Some(DnsRecord::Txt(strings))
+            }
+            ResourceType::Srv => Some(DnsRecord::Srv {
+                priority: u16_at(0)?,
+                weight: u16_at(2)?,
+                port: u16_at(4)?,
+                target: name_at(6)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A resource record of a response, with the domain name it belongs to
+#[derive(Debug)]
+struct Answer {
+    /// The domain name the record belongs to
+    owner: String,
+    /// The record
+    record: DnsRecord,
+    /// The time the record can be cached for, in seconds
+    ttl: u32,
+}
+
+impl Answer {
+    /// Whether the record belongs to `name`
+    fn is_owned_by(&self, name: &str) -> bool {
+        self.owner.eq_ignore_ascii_case(name)
+    }
+}
+
+/// An IPv4 address a hostname resolved to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedAddr {
+    /// The address
+    pub addr: Ipv4Addr,
+    /// The time the address can be cached for
+    pub ttl: Duration,
+}
+
 /// An error that can occur when using a DNS resolver
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum DnsError {
     /// The DNS resolver failed to create
-    #[error("Failed to create DNS resolver: {}", 0)]
+    #[error("Failed to create DNS resolver: {0}")]
     FailedToCreate(String),
     /// The hostname could not be resolved
-    #[error("Hostname resolution failed: {}", 0)]
+    #[error("Hostname resolution failed: {0}")]
     HostnameResolutionFailed(String),
     /// The IP address could not be resolved
-    #[error("Address resolution failed: {}", 0)]
+    #[error("Address resolution failed: {0}")]
     AddressResolutionFailed(String),
+    /// None of the addresses of the hostname accepted the connection
+    #[error("Connection failed: {0}")]
+    ConnectionFailed(String),
+    /// The domain does not exist, or has no record of the requested type
+    #[error("No {ty:?} record for {name}")]
+    NoRecord {
+        /// The domain queried, or the canonical name it is an alias of
+        name: String,
+        /// The type of the records queried
+        ty: ResourceType,
+    },
+}
+
+impl DnsError {
+    /// Whether the error is an authoritative negative answer: the domain does
+    /// not exist, or has no record of the requested type
+    ///
+    /// Unlike a timeout, a server failure or a socket error, such an answer
+    /// stays true for a while, and can be cached.
+    #[must_use]
+    pub fn is_negative_answer(&self) -> bool {
+        matches!(self, DnsError::NoRecord { .. })
+    }
 }
 
 /// A DNS resolver
+///
+/// Queries are sent to the DNS servers in order: when a server does not
+/// answer within the timeout, or fails, the next one is queried. Once every
+/// server failed, they are all queried again, up to the retry count, waiting
+/// longer before each round.
 pub struct DnsResolver {
     /// The UDP socket that is used to send and receive DNS messages
-    udp_socket: UdpSocket<Connected>,
-    /// The DNS server address
-    dns: SocketAddr,
+    udp_socket: UdpSocket<Bound>,
+    /// The DNS server addresses, in the order they are queried
+    servers: Vec<SocketAddr>,
+    /// The time waited for an answer, per attempt
+    timeout: Duration,
+    /// The number of times the servers are queried again after they all failed
+    retries: usize,
+    /// The time waited before querying the servers again, doubled each round
+    backoff: Duration,
+    /// The ID of the next query
+    next_id: u16,
 }
 
 impl DnsResolver {
@@ -63,16 +320,37 @@ impl DnsResolver {
     ///   happen if the socket could not be created or bound to the specified address
     #[allow(unused)]
     pub fn new(dns: SocketAddr) -> Result<Self, DnsError> {
+        Self::with_servers(&[dns])
+    }
+
+    /// Create a new DNS resolver, querying several DNS servers
+    ///
+    /// # Parameters
+    /// - `servers`: The [`SocketAddr`]s of the DNS servers, in the order they
+    ///   are queried
+    ///
+    /// # Errors
+    /// - [`DnsError::FailedToCreate`]: The DNS resolver failed to create. This may
+    ///   happen if `servers` is empty, or if the socket could not be created or
+    ///   bound
+    pub fn with_servers(servers: &[SocketAddr]) -> Result<Self, DnsError> {
+        if servers.is_empty() {
+            return Err(DnsError::FailedToCreate("No DNS server given".to_owned()));
+        }
         let udp_socket = UdpSocket::new()
             .map_err(|_| DnsError::FailedToCreate("Failed to create socket".to_owned()))?;
         let udp_socket = udp_socket
             .bind(None) // binds to None, otherwise the socket errors for some reason
             .map_err(|_| DnsError::FailedToCreate("Failed to bind socket".to_owned()))?;
-        let udp_socket = udp_socket
-            .connect(dns)
-            .map_err(|_| DnsError::FailedToCreate("Failed to connect socket".to_owned()))?;
 
-        Ok(DnsResolver { udp_socket, dns })
+        Ok(DnsResolver {
+            udp_socket,
+            servers: servers.to_vec(),
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            next_id: 0x42,
+        })
     }
 
     /// Try to create a new DNS resolver with default settings
@@ -87,6 +365,8 @@ impl DnsResolver {
 
     /// Resolve a hostname to an IP address
     ///
+    /// CNAME records are followed to the A record of the canonical name.
+    ///
     /// # Parameters
     /// - `host`: The hostname to resolve
     ///
@@ -98,83 +378,370 @@ impl DnsResolver {
     /// - [`DnsError::HostnameResolutionFailed`]: The hostname could not be resolved.
     ///   This may happen if the connection of the socket fails, or if the DNS server
     ///   does not answer the query, or any other error occurs
+    /// - [`DnsError::NoRecord`]: The hostname does not exist, or has no A record
     pub fn resolve(&mut self, host: &str) -> Result<in_addr, DnsError> {
-        // create a new query
-        let mut questions = [super::dns::create_a_type_query(host)];
-        let query = dns_protocol::Message::new(
-            0x42,
-            Flags::standard_query(),
-            &mut questions,
-            &mut [],
-            &mut [],
-            &mut [],
-        );
+        match self.query(host, ResourceType::A)?.first() {
+            Some(DnsRecord::A(addr)) => Ok(in_addr(u32::from(*addr))),
+            _ => Err(DnsError::HostnameResolutionFailed(
+                "Could not parse IP address".to_owned(),
+            )),
+        }
+    }
 
-        // create a new buffer with the size of the message
-        let mut tx_buf = a_vec![0u8; query.space_needed()];
-        // serialize the message into the buffer
-        query.write(&mut tx_buf).map_err(|_| {
-            DnsError::HostnameResolutionFailed("Could not serialize query".to_owned())
-        })?;
+    /// Resolve a hostname to all of its IP addresses
+    ///
+    /// CNAME records are followed to the A records of the canonical name.
+    ///
+    /// # Parameters
+    /// - `host`: The hostname to resolve
+    ///
+    /// # Returns
+    /// - `Ok(Vec<ResolvedAddr>)`: The IP addresses of the hostname, in the order
+    ///   of the response, with the time they can be cached for. It is never empty.
+    ///
+    /// # Errors
+    /// - [`DnsError::HostnameResolutionFailed`]: The hostname could not be resolved.
+    ///   This may happen if the connection of the socket fails, or if the DNS server
+    ///   does not answer the query, or any other error occurs
+    /// - [`DnsError::NoRecord`]: The hostname does not exist, or has no A record
+    pub fn resolve_all(&mut self, host: &str) -> Result<Vec<ResolvedAddr>, DnsError> {
+        let records = self.query_with(host, ResourceType::A, DnsError::HostnameResolutionFailed)?;
+        Ok(records
+            .into_iter()
+            .filter_map(|(record, ttl)| match record {
+                DnsRecord::A(addr) => Some(ResolvedAddr {
+                    addr,
+                    ttl: Duration::from_secs(u64::from(ttl)),
+                }),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Resolve a hostname, and connect to the first of its IP addresses
+    /// accepting the connection
+    ///
+    /// The addresses are tried in the order of the response, so that hosts
+    /// whose addresses are rotated, or partly down, can be reached.
+    ///
+    /// # Parameters
+    /// - `host`: The hostname to connect to
+    /// - `port`: The port to connect to
+    ///
+    /// # Errors
+    /// - [`DnsError::HostnameResolutionFailed`]: The hostname could not be resolved
+    /// - [`DnsError::NoRecord`]: The hostname does not exist, or has no A record
+    /// - [`DnsError::ConnectionFailed`]: None of the addresses accepted the
+    ///   connection. It describes the error of the last address tried.
+    pub fn connect(&mut self, host: &str, port: u16) -> Result<TcpSocket<Connected>, DnsError> {
+        let addrs = self.resolve_all(host)?;
+        TcpSocket::connect_any(
+            addrs
+                .iter()
+                .map(|resolved| SocketAddr::new(IpAddr::V4(resolved.addr), port)),
+        )
+        .map_err(|e| DnsError::ConnectionFailed(e.to_string()))
+    }
 
-        // send the message to the DNS server
-        let _ = self
-            .udp_socket
-            .write(&tx_buf)
-            .map_err(|e| DnsError::HostnameResolutionFailed(e.to_string()))?;
+    /// Resolve an IP address to a hostname, with a PTR query
+    ///
+    /// # Parameters
+    /// - `addr`: The IP address to resolve
+    ///
+    /// # Returns
+    /// - `Ok(String)`: The hostname of the IP address
+    ///
+    /// # Errors
+    /// - [`DnsError::AddressResolutionFailed`]: The IP address could not be resolved.
+    ///   This may happen if the DNS server does not answer the query, or any
+    ///   other error occurs
+    /// - [`DnsError::NoRecord`]: The DNS server has no PTR record for the address
+    pub fn resolve_ptr(&mut self, addr: &in_addr) -> Result<String, DnsError> {
+        let name = reverse_name(addr);
+        let records =
+            self.query_with(&name, ResourceType::Ptr, DnsError::AddressResolutionFailed)?;
+        match records.into_iter().next() {
+            Some((DnsRecord::Ptr(host), _)) => Ok(host),
+            _ => Err(DnsError::AddressResolutionFailed(
+                "Could not parse domain name".to_owned(),
+            )),
+        }
+    }
 
-        let mut rx_buf = [0u8; 1024];
+    /// Query the records of type `ty` of a domain
+    ///
+    /// CNAME records are followed to the records of the canonical name, querying
+    /// it if the response does not include them.
+    ///
+    /// # Parameters
+    /// - `name`: The domain name to query
+    /// - `ty`: The type of the records
+    ///
+    /// # Returns
+    /// - `Ok(Vec<DnsRecord>)`: The records, in the order of the response. It is
+    ///   never empty.
+    ///
+    /// # Errors
+    /// - [`DnsError::HostnameResolutionFailed`]: The connection of the socket
+    ///   fails, the DNS server does not answer the query, or any other error occurs
+    /// - [`DnsError::NoRecord`]: The domain does not exist, or has no record of
+    ///   type `ty`
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut resolver = DnsResolver::try_default()?;
+    /// for record in resolver.query("_game._udp.example.com", ResourceType::Srv)? {
+    ///     if let DnsRecord::Srv { port, target, .. } = record {
+    ///         // ...
+    ///     }
+    /// }
+    /// ```
+    pub fn query(&mut self, name: &str, ty: ResourceType) -> Result<Vec<DnsRecord>, DnsError> {
+        let records = self.query_with(name, ty, DnsError::HostnameResolutionFailed)?;
+        Ok(records.into_iter().map(|(record, _)| record).collect())
+    }
+
+    /// Query the records of type `ty` of a domain, following CNAME records
+    ///
+    /// See [`DnsResolver::query`], `error` creating the error returned on
+    /// failure, from its description.
+    ///
+    /// # Returns
+    /// The records, with the time they can be cached for, in seconds. It is
+    /// the shortest TTL of the record and of the CNAME records followed.
+    fn query_with(
+        &mut self,
+        name: &str,
+        ty: ResourceType,
+        error: fn(String) -> DnsError,
+    ) -> Result<Vec<(DnsRecord, u32)>, DnsError> {
+        let mut name = name.to_owned();
+        let mut answers = self.lookup(&name, ty, error)?;
+        let mut alias_ttl = u32::MAX;
+
+        for _ in 0..=MAX_CNAME_HOPS {
+            let found: Vec<(DnsRecord, u32)> = answers
+                .iter()
+                .filter(|answer| answer.is_owned_by(&name) && answer.record.resource_type() == ty)
+                .map(|answer| (answer.record.clone(), answer.ttl.min(alias_ttl)))
+                .collect();
+            if !found.is_empty() {
+                return Ok(found);
+            }
 
-        // receive the response from the DNS server
-        let data_len = self
-            .udp_socket
-            .read(&mut rx_buf)
-            .map_err(|e| DnsError::HostnameResolutionFailed(e.to_string()))?;
+            let alias = answers.iter().find_map(|answer| match &answer.record {
+                DnsRecord::Cname(target) if answer.is_owned_by(&name) => {
+                    Some((target.clone(), answer.ttl))
+                }
+                _ => None,
+            });
+            let Some((alias, ttl)) = alias else {
+                return Err(DnsError::NoRecord { name, ty });
+            };
 
-        if data_len == 0 {
-            return Err(DnsError::HostnameResolutionFailed(
-                "No data received".to_owned(),
-            ));
+            name = alias;
+            alias_ttl = alias_ttl.min(ttl);
+            if !answers.iter().any(|answer| answer.is_owned_by(&name)) {
+                answers = self.lookup(&name, ty, error)?;
+            }
         }
 
+        Err(error(format!("Too many CNAME records for {name}")))
+    }
+
+    /// Send a query for the records of type `ty` of a domain, and parse the
+    /// answers of the response
+    ///
+    /// # Returns
+    /// The answers of a supported type
+    fn lookup(
+        &mut self,
+        name: &str,
+        ty: ResourceType,
+        error: fn(String) -> DnsError,
+    ) -> Result<Vec<Answer>, DnsError> {
+        let mut rx_buf = [0u8; 1024];
+        let mut questions = [create_query(name, ty)];
+        let data_len = self.exchange(&mut questions, &mut rx_buf, error)?;
+        let response = &rx_buf[..data_len];
+
         // parse the response
         let mut answers = [ResourceRecord::default(); 16];
         let mut authority = [ResourceRecord::default(); 16];
         let mut additional = [ResourceRecord::default(); 16];
         let message = dns_protocol::Message::read(
-            &rx_buf[..data_len],
+            response,
             &mut questions,
             &mut answers,
             &mut authority,
             &mut additional,
         )
-        .map_err(|_| DnsError::HostnameResolutionFailed("Could not parse response".to_owned()))?;
+        .map_err(|_| error("Could not parse response".to_owned()))?;
+
+        Ok(message
+            .answers()
+            .iter()
+            .filter_map(|answer| {
+                Some(Answer {
+                    owner: answer.name().to_string(),
+                    record: DnsRecord::parse(response, answer)?,
+                    ttl: answer.ttl(),
+                })
+            })
+            .collect())
+    }
+
+    /// Send a query, and receive the response
+    ///
+    /// The servers are tried in order, then again after a backoff, up to the
+    /// retry count.
+    ///
+    /// # Parameters
+    /// - `questions`: The questions of the query
+    /// - `rx_buf`: The buffer the response is received into
+    /// - `error`: Creates the error returned on failure, from its description
+    ///
+    /// # Returns
+    /// The length of the response
+    ///
+    /// # Errors
+    /// The error created by `error` lists every attempt, and why it failed.
+    fn exchange(
+        &mut self,
+        questions: &mut [Question<'_>],
+        rx_buf: &mut [u8],
+        error: fn(String) -> DnsError,
+    ) -> Result<usize, DnsError> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        // create a new query
+        let query = dns_protocol::Message::new(
+            id,
+            Flags::standard_query(),
+            questions,
+            &mut [],
+            &mut [],
+            &mut [],
+        );
+
+        // create a new buffer with the size of the message
+        let mut tx_buf = a_vec![0u8; query.space_needed()];
+        // serialize the message into the buffer
+        query
+            .write(&mut tx_buf)
+            .map_err(|_| error("Could not serialize query".to_owned()))?;
+
+        let mut failures = Vec::new();
+        let mut backoff = self.backoff;
+        for round in 0..=self.retries {
+            if round > 0 {
+                delay(backoff);
+                backoff = backoff.saturating_mul(2);
+            }
 
-        if message.answers().is_empty() {
-            return Err(DnsError::HostnameResolutionFailed(
-                "No answers received".to_owned(),
-            ));
+            for &server in &self.servers {
+                match self.attempt(server, id, &tx_buf, rx_buf) {
+                    Ok(data_len) => return Ok(data_len),
+                    Err(reason) => failures.push(format!("{server}: {reason}")),
+                }
+            }
         }
-        let answer = message.answers()[0];
-
-        match answer.data().len() {
-            4 => {
-                let mut octets = [0u8; 4];
-                octets.copy_from_slice(answer.data());
-                let addr = in_addr(u32::from_be_bytes(octets));
-                Ok(addr)
+
+        Err(error(format!(
+            "No DNS server answered ({})",
+            failures.join(", ")
+        )))
+    }
+
+    /// Send a query to a single server, and wait for its response
+    ///
+    /// Datagrams which are not the response of `server` to the query `id`, such
+    /// as late responses to previous attempts, are discarded.
+    ///
+    /// # Returns
+    /// The length of the response, or why the attempt failed
+    fn attempt(
+        &self,
+        server: SocketAddr,
+        id: u16,
+        query: &[u8],
+        rx_buf: &mut [u8],
+    ) -> Result<usize, String> {
+        self.udp_socket
+            .send_to(query, server)
+            .map_err(|e| e.to_string())?;
+
+        let start = DefaultBackend::now_micros();
+        loop {
+            let elapsed = Duration::from_micros(DefaultBackend::now_micros().saturating_sub(start));
+            let Some(remaining) = self.timeout.checked_sub(elapsed).filter(|d| !d.is_zero()) else {
+                return Err("timed out".to_owned());
+            };
+            let (readiness, _) = poll::wait(
+                self.udp_socket.socket_fd(),
+                Interest::READABLE,
+                Some(remaining),
+            )
+            .map_err(|e| e.to_string())?;
+            if readiness.is_empty() {
+                return Err("timed out".to_owned());
+            }
+
+            let (data_len, from) = self
+                .udp_socket
+                .recv_from(rx_buf)
+                .map_err(|e| e.to_string())?;
+            let response = &rx_buf[..data_len];
+            if from != server || data_len < 12 || response[..2] != id.to_be_bytes() {
+                continue;
+            }
+
+            // the server could not answer: another one may
+            match response[3] & 0x0f {
+                1 => return Err("format error".to_owned()),
+                2 => return Err("server failure".to_owned()),
+                4 => return Err("not implemented".to_owned()),
+                5 => return Err("query refused".to_owned()),
+                _ => return Ok(data_len),
             }
-            _ => Err(DnsError::HostnameResolutionFailed(
-                "Could not parse IP address".to_owned(),
-            )),
         }
     }
 
-    /// Get the [`SocketAddr`] of the DNS server
+    /// Set the time waited for the answer of a server, per attempt
+    ///
+    /// Defaults to [`DEFAULT_TIMEOUT`].
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Set the number of times the servers are queried again after they all
+    /// failed
+    ///
+    /// Defaults to [`DEFAULT_RETRIES`].
+    pub fn set_retries(&mut self, retries: usize) {
+        self.retries = retries;
+    }
+
+    /// Set the time waited before querying the servers again
+    ///
+    /// It is doubled after each round. Defaults to [`DEFAULT_BACKOFF`].
+    pub fn set_backoff(&mut self, backoff: Duration) {
+        self.backoff = backoff;
+    }
+
+    /// Get the [`SocketAddr`]s of the DNS servers, in the order they are queried
+    #[must_use]
+    #[inline]
+    pub fn servers(&self) -> &[SocketAddr] {
+        &self.servers
+    }
+
+    /// Get the [`SocketAddr`] of the first DNS server
     #[must_use]
     #[inline]
     pub fn dns(&self) -> SocketAddr {
-        self.dns
+        self.servers[0]
     }
 }
 
@@ -194,17 +761,330 @@ impl traits::dns::ResolveHostname for DnsResolver {
     /// - [`DnsError::HostnameResolutionFailed`]: The hostname could not be resolved.
     ///   This may happen if the connection of the socket fails, or if the DNS server
     ///   does not answer the query, or any other error occurs
+    /// - [`DnsError::NoRecord`]: The hostname does not exist, or has no A record
     fn resolve_hostname(&mut self, hostname: &str) -> Result<SocketAddr, DnsError> {
         self.resolve(hostname).map(|addr| addr.to_socket_addr())
     }
+
+    /// Resolve a hostname to an IP address, with the time it can be cached for
+    ///
+    /// The time is the TTL of the A record, and of the CNAME records followed
+    /// to get to it. See [`DnsResolver::resolve_all`].
+    ///
+    /// # Errors
+    /// - [`DnsError::HostnameResolutionFailed`]: The hostname could not be resolved
+    fn resolve_hostname_with_ttl(
+        &mut self,
+        hostname: &str,
+    ) -> Result<(SocketAddr, Option<Duration>), DnsError> {
+        let resolved = self.resolve_all(hostname)?[0];
+        let addr = in_addr(u32::from(resolved.addr)).to_socket_addr();
+        Ok((addr, Some(resolved.ttl)))
+    }
+
+    /// Whether the domain does not exist, or has no A record
+    ///
+    /// See [`DnsError::is_negative_answer`].
+    fn is_negative_answer(error: &DnsError) -> bool {
+        error.is_negative_answer()
+    }
 }
 
 impl traits::dns::ResolveAddr for DnsResolver {
     type Error = DnsError;
 
-    fn resolve_addr(&mut self, _addr: in_addr) -> Result<String, DnsError> {
-        todo!("resolve_addr")
+    /// Resolve an IP address to a hostname
+    ///
+    /// See [`DnsResolver::resolve_ptr`].
+    ///
+    /// # Errors
+    /// - [`DnsError::AddressResolutionFailed`]: The IP address could not be resolved
+    fn resolve_addr(&mut self, addr: in_addr) -> Result<String, DnsError> {
+        self.resolve_ptr(&addr)
     }
 }
 
 impl traits::dns::DnsResolver for DnsResolver {}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+    use alloc::{vec, vec::Vec};
+    use dns_protocol::{Message, MessageType};
+
+    /// The owner of an answer named after the question, which follows the
+    /// header at offset 12
+    const QUESTION: &[u8] = b"\xc0\x0c";
+
+    /// Spawn a DNS server answering a single query with `answers`, given as
+    /// their owner, type and data
+    fn dns_server(answers: Vec<(&'static [u8], u16, Vec<u8>)>) -> SocketAddr {
+        lossy_dns_server(0, answers)
+    }
+
+    /// Spawn a DNS server ignoring the first `dropped` queries, then answering
+    /// a single query with `answers`
+    fn lossy_dns_server(dropped: usize, answers: Vec<(&'static [u8], u16, Vec<u8>)>) -> SocketAddr {
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            for _ in 0..dropped {
+                server.recv_from(&mut buf).unwrap();
+            }
+            let (n, from) = server.recv_from(&mut buf).unwrap();
+
+            let mut questions = [Question::default()];
+            let id = Message::read(&buf[..n], &mut questions, &mut [], &mut [], &mut [])
+                .unwrap()
+                .id();
+            let mut flags = Flags::standard_query();
+            flags.set_qr(MessageType::Reply);
+            let reply = Message::new(id, flags, &mut questions, &mut [], &mut [], &mut []);
+            let mut tx_buf = a_vec![0u8; reply.space_needed()];
+            reply.write(&mut tx_buf).unwrap();
+
+            // dns-protocol 0.1.1 cannot serialize resource records, so the
+            // answers are appended by hand
+            tx_buf[6..8].copy_from_slice(&(answers.len() as u16).to_be_bytes());
+            for (owner, ty, data) in answers {
+                tx_buf.extend_from_slice(owner);
+                tx_buf.extend_from_slice(&ty.to_be_bytes());
+                tx_buf.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+                tx_buf.extend_from_slice(&(data.len() as u16).to_be_bytes());
+                tx_buf.extend_from_slice(&data);
+            }
+            server.send_to(&tx_buf, from).unwrap();
+        });
+        addr
+    }
+
+    #[test]
+    fn resolve_a_record() {
+        let addr = dns_server(vec![(QUESTION, 1, vec![10, 0, 0, 1])]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let ip = resolver.resolve("example.com").unwrap();
+        assert_eq!(ip.0, u32::from_be_bytes([10, 0, 0, 1]));
+    }
+
+    #[test]
+    fn resolve_ptr_record() {
+        use traits::dns::ResolveAddr;
+
+        // "psp" followed by a pointer to the question, at offset 12
+        let addr = dns_server(vec![(QUESTION, 12, b"\x03psp\xc0\x0c".to_vec())]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let name = resolver
+            .resolve_addr(in_addr(u32::from_be_bytes([10, 0, 0, 1])))
+            .unwrap();
+        assert_eq!(name, "psp.1.0.0.10.in-addr.arpa");
+    }
+
+    #[test]
+    fn missing_ptr_record_is_an_error() {
+        let addr = dns_server(vec![]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let result = resolver.resolve_ptr(&in_addr(u32::from_be_bytes([10, 0, 0, 1])));
+        assert!(matches!(result, Err(DnsError::NoRecord { .. })));
+        assert!(result.unwrap_err().is_negative_answer());
+    }
+
+    #[test]
+    fn resolve_follows_cname() {
+        let canonical: &[u8] = b"\x03cdn\x07example\x03net\x00";
+        let addr = dns_server(vec![
+            (QUESTION, 5, canonical.to_vec()),
+            (canonical, 1, vec![10, 0, 0, 2]),
+        ]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let ip = resolver.resolve("www.example.com").unwrap();
+        assert_eq!(ip.0, u32::from_be_bytes([10, 0, 0, 2]));
+    }
+
+    #[test]
+    fn query_typed_records() {
+        let mut ipv6 = [0u8; 16];
+        ipv6[..2].copy_from_slice(&[0xfe, 0x80]);
+        ipv6[15] = 1;
+
+        let cases = [
+            (
+                ResourceType::AAAA,
+                ipv6.to_vec(),
+                DnsRecord::Aaaa(Ipv6Addr::from(ipv6)),
+            ),
+            (
+                ResourceType::MX,
+                b"\x00\x0a\x04mail\xc0\x0c".to_vec(),
+                DnsRecord::Mx {
+                    preference: 10,
+                    exchange: "mail.example.com".to_owned(),
+                },
+            ),
+            (
+                ResourceType::Txt,
+                b"\x05hello\x05world".to_vec(),
+                DnsRecord::Txt(vec!["hello".to_owned(), "world".to_owned()]),
+            ),
+            (
+                ResourceType::Srv,
+                b"\x00\x01\x00\x05\x0f\xa0\x04game\xc0\x0c".to_vec(),
+                DnsRecord::Srv {
+                    priority: 1,
+                    weight: 5,
+                    port: 4000,
+                    target: "game.example.com".to_owned(),
+                },
+            ),
+        ];
+
+        for (ty, data, expected) in cases {
+            let addr = dns_server(vec![(QUESTION, ty.into(), data)]);
+            let mut resolver = DnsResolver::new(addr).unwrap();
+            let records = resolver.query("example.com", ty).unwrap();
+            assert_eq!(records, vec![expected]);
+        }
+    }
+
+    #[test]
+    fn missing_record_type_is_an_error() {
+        let addr = dns_server(vec![(QUESTION, 1, vec![10, 0, 0, 1])]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let result = resolver.query("example.com", ResourceType::AAAA);
+        assert_eq!(
+            result,
+            Err(DnsError::NoRecord {
+                name: "example.com".to_owned(),
+                ty: ResourceType::AAAA,
+            })
+        );
+        assert!(result.unwrap_err().is_negative_answer());
+    }
+
+    #[test]
+    fn resolve_all_returns_every_address() {
+        let addr = dns_server(vec![
+            (QUESTION, 1, vec![10, 0, 0, 1]),
+            (QUESTION, 1, vec![10, 0, 0, 2]),
+        ]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let addrs = resolver.resolve_all("example.com").unwrap();
+        let ttl = Duration::from_secs(60);
+        assert_eq!(
+            addrs,
+            vec![
+                ResolvedAddr {
+                    addr: Ipv4Addr::new(10, 0, 0, 1),
+                    ttl
+                },
+                ResolvedAddr {
+                    addr: Ipv4Addr::new(10, 0, 0, 2),
+                    ttl
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn connect_tries_each_address() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // nothing listens on 127.0.0.2, which refuses the connection
+        let addr = dns_server(vec![
+            (QUESTION, 1, vec![127, 0, 0, 2]),
+            (QUESTION, 1, vec![127, 0, 0, 1]),
+        ]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let socket = resolver.connect("example.com", port).unwrap();
+        assert_eq!(socket.peer_addr().unwrap(), listener.local_addr().unwrap());
+    }
+
+    #[test]
+    fn connect_error_describes_the_last_failure() {
+        // nothing listens on 127.0.0.2, which refuses the connection
+        let addr = dns_server(vec![(QUESTION, 1, vec![127, 0, 0, 2])]);
+        let mut resolver = DnsResolver::new(addr).unwrap();
+        let error = resolver.connect("example.com", 9).unwrap_err();
+        assert_eq!(error.to_string(), "Connection failed: Connection refused");
+    }
+
+    /// Create a resolver with short timeouts, querying `servers`
+    fn impatient_resolver(servers: &[SocketAddr]) -> DnsResolver {
+        let mut resolver = DnsResolver::with_servers(servers).unwrap();
+        resolver.set_timeout(Duration::from_millis(50));
+        resolver.set_backoff(Duration::from_millis(10));
+        resolver
+    }
+
+    #[test]
+    fn unanswered_query_fails_over_to_next_server() {
+        // never answers, but stays bound
+        let silent = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = dns_server(vec![(QUESTION, 1, vec![10, 0, 0, 1])]);
+
+        let mut resolver = impatient_resolver(&[silent.local_addr().unwrap(), addr]);
+        let ip = resolver.resolve("example.com").unwrap();
+        assert_eq!(ip.0, u32::from_be_bytes([10, 0, 0, 1]));
+    }
+
+    #[test]
+    fn error_response_fails_over_to_next_server() {
+        // format error, server failure, not implemented, refused
+        for rcode in [1, 2, 4, 5] {
+            let failing = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let failing_addr = failing.local_addr().unwrap();
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 512];
+                let (n, from) = failing.recv_from(&mut buf).unwrap();
+                // answer with the query itself, flagged as a response
+                buf[2] |= 0x80;
+                buf[3] = (buf[3] & 0xf0) | rcode;
+                failing.send_to(&buf[..n], from).unwrap();
+            });
+            let addr = dns_server(vec![(QUESTION, 1, vec![10, 0, 0, 1])]);
+
+            let mut resolver = impatient_resolver(&[failing_addr, addr]);
+            let ip = resolver.resolve("example.com").unwrap();
+            assert_eq!(ip.0, u32::from_be_bytes([10, 0, 0, 1]), "rcode {rcode}");
+        }
+    }
+
+    #[test]
+    fn lost_query_is_retried() {
+        let addr = lossy_dns_server(1, vec![(QUESTION, 1, vec![10, 0, 0, 1])]);
+
+        let mut resolver = impatient_resolver(&[addr]);
+        let ip = resolver.resolve("example.com").unwrap();
+        assert_eq!(ip.0, u32::from_be_bytes([10, 0, 0, 1]));
+    }
+
+    #[test]
+    fn error_lists_every_attempt() {
+        let first = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let second = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let servers = [first.local_addr().unwrap(), second.local_addr().unwrap()];
+
+        let mut resolver = impatient_resolver(&servers);
+        resolver.set_retries(1);
+        let Err(error) = resolver.resolve("example.com") else {
+            panic!("resolution should fail");
+        };
+        let DnsError::HostnameResolutionFailed(reason) = &error else {
+            panic!("unexpected error: {error}");
+        };
+        let attempt = |server: &SocketAddr| format!("{server}: timed out");
+        let expected = format!(
+            "No DNS server answered ({}, {}, {}, {})",
+            attempt(&servers[0]),
+            attempt(&servers[1]),
+            attempt(&servers[0]),
+            attempt(&servers[1]),
+        );
+        assert_eq!(*reason, expected);
+        assert_eq!(
+            error.to_string(),
+            format!("Hostname resolution failed: {expected}")
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use alloc::{string::String, vec::Vec};
+use core::{net::SocketAddr, time::Duration};
+
+use crate::{
+    backend::{DefaultBackend, NetBackend},
+    netc::in_addr,
+    traits::dns::{self, ResolveAddr, ResolveHostname},
+};
+
+/// Default maximum number of hostnames cached by a [`CachingResolver`]
+pub const DEFAULT_CACHE_CAPACITY: usize = 32;
+
+/// Default time answers are cached for, when the resolver does not give one
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// Default time negative answers are cached for
+pub const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(30);
+
+/// The cached answer for a hostname
+#[derive(Debug)]
+struct Entry<E> {
+    /// The hostname, in lowercase
+    hostname: String,
+    /// The address of the hostname, or the error resolving it
+    answer: Result<SocketAddr, E>,
+    /// When the answer expires, in microseconds (see [`NetBackend::now_micros`])
+    expires: u64,
+}
+
+/// A DNS cache wrapping any [`ResolveHostname`]
+///
+/// Resolved addresses are cached until their TTL expires. Errors the wrapped
+/// resolver classifies as negative answers (see
+/// [`ResolveHostname::is_negative_answer`]), such as a hostname that does not
+/// exist, are cached for [`CachingResolver::set_negative_ttl`]. Other errors,
+/// such as timeouts, server failures or socket errors, are never cached: the
+/// next resolution queries again. Once the cache is full, the least recently
+/// used hostname is evicted.
+///
+/// # Example
+/// ```ignore
+/// let mut resolver = CachingResolver::new(DnsResolver::try_default()?);
+/// // sends a query
+/// let addr = resolver.resolve_hostname("api.example.com")?;
+/// // answered from the cache
+/// let addr = resolver.resolve_hostname("api.example.com")?;
+/// ```
+#[derive(Debug)]
+pub struct CachingResolver<R: ResolveHostname> {
+    /// The wrapped resolver
+    resolver: R,
+    /// The cached answers, the most recently used last
+    entries: Vec<Entry<R::Error>>,
+    /// The maximum number of cached answers
+    capacity: usize,
+    /// The time answers are cached for, when the resolver does not give one
+    default_ttl: Duration,
+    /// The time negative answers are cached for
+    negative_ttl: Duration,
+}
+
+impl<R: ResolveHostname> CachingResolver<R> {
+    /// Create a new cache, holding up to [`DEFAULT_CACHE_CAPACITY`] hostnames
+    ///
+    /// # Parameters
+    /// - `resolver`: The resolver queried on cache misses
+    #[must_use]
+    pub fn new(resolver: R) -> Self {
+        Self::with_capacity(resolver, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Create a new cache, holding up to `capacity` hostnames
+    ///
+    /// # Parameters
+    /// - `resolver`: The resolver queried on cache misses
+    /// - `capacity`: The maximum number of cached hostnames
+    #[must_use]
+    pub fn with_capacity(resolver: R, capacity: usize) -> Self {
+        Self {
+            resolver,
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            default_ttl: DEFAULT_TTL,
+            negative_ttl: DEFAULT_NEGATIVE_TTL,
+        }
+    }
+
+    /// Set the time answers are cached for, when the resolver does not give one
+    ///
+    /// Defaults to [`DEFAULT_TTL`].
+    pub fn set_default_ttl(&mut self, ttl: Duration) {
+        self.default_ttl = ttl;
+    }
+
+    /// Set the time negative answers are cached for
+    ///
+    /// Defaults to [`DEFAULT_NEGATIVE_TTL`]. A zero duration disables negative
+    /// caching.
+    pub fn set_negative_ttl(&mut self, ttl: Duration) {
+        self.negative_ttl = ttl;
+    }
+
+    /// Cache the address of a hostname, without resolving it
+    ///
+    /// # Parameters
+    /// - `hostname`: The hostname
+    /// - `addr`: The address of the hostname
+    /// - `ttl`: The time the address is cached for
+    pub fn seed(&mut self, hostname: &str, addr: SocketAddr, ttl: Duration) {
+        self.insert(hostname, Ok(addr), ttl);
+    }
+
+    /// Remove the cached answer for a hostname
+    pub fn forget(&mut self, hostname: &str) {
+        self.entries
+            .retain(|entry| !entry.hostname.eq_ignore_ascii_case(hostname));
+    }
+
+    /// Remove all the cached answers
+    pub fn flush(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The number of cached answers, including the expired ones not evicted yet
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no answer is cached
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get a reference to the wrapped resolver
+    #[must_use]
+    pub fn resolver(&self) -> &R {
+        &self.resolver
+    }
+
+    /// Get a mutable reference to the wrapped resolver
+    pub fn resolver_mut(&mut self) -> &mut R {
+        &mut self.resolver
+    }
+
+    /// Get the wrapped resolver back, dropping the cache
+    pub fn into_inner(self) -> R {
+        self.resolver
+    }
+
+    /// Cache an answer for `ttl`, evicting the least recently used answer if
+    /// the cache is full
+    fn insert(&mut self, hostname: &str, answer: Result<SocketAddr, R::Error>, ttl: Duration) {
+        self.forget(hostname);
+        if self.capacity == 0 || ttl.is_zero() {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+
+        let ttl = u64::try_from(ttl.as_micros()).unwrap_or(u64::MAX);
+        self.entries.push(Entry {
+            hostname: hostname.to_ascii_lowercase(),
+            answer,
+            expires: DefaultBackend::now_micros().saturating_add(ttl),
+        });
+    }
+}
+
+impl<R: ResolveHostname> ResolveHostname for CachingResolver<R>
+where
+    R::Error: Clone,
+{
+    type Error = R::Error;
+
+    /// Resolve a hostname to an IP address, from the cache if possible
+    ///
+    /// # Errors
+    /// The error of the wrapped resolver, cached if it is a negative answer.
+    fn resolve_hostname(&mut self, hostname: &str) -> Result<SocketAddr, R::Error> {
+        let now = DefaultBackend::now_micros();
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|entry| entry.hostname.eq_ignore_ascii_case(hostname))
+        {
+            let entry = self.entries.remove(index);
+            if now < entry.expires {
+                let answer = entry.answer.clone();
+                self.entries.push(entry);
+                return answer;
+            }
+        }
+
+        match self.resolver.resolve_hostname_with_ttl(hostname) {
+            Ok((addr, ttl)) => {
+                self.insert(hostname, Ok(addr), ttl.unwrap_or(self.default_ttl));
+                Ok(addr)
+            }
+            Err(error) if R::is_negative_answer(&error) => {
+                self.insert(hostname, Err(error.clone()), self.negative_ttl);
+                Err(error)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    fn is_negative_answer(error: &R::Error) -> bool {
+        R::is_negative_answer(error)
+    }
+}
+
+impl<R: ResolveHostname + ResolveAddr> ResolveAddr for CachingResolver<R> {
+    type Error = <R as ResolveAddr>::Error;
+
+    /// Resolve an IP address to a hostname, with the wrapped resolver
+    ///
+    /// Reverse lookups are not cached.
+    ///
+    /// # Errors
+    /// The error of the wrapped resolver.
+    fn resolve_addr(&mut self, addr: in_addr) -> Result<String, Self::Error> {
+        self.resolver.resolve_addr(addr)
+    }
+}
+
+impl<R: dns::DnsResolver> dns::DnsResolver for CachingResolver<R> where
+    <R as ResolveHostname>::Error: Clone
+{
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use alloc::borrow::ToOwned;
+    use core::net::{IpAddr, Ipv4Addr};
+
+    use super::*;
+
+    /// A resolver counting its queries, resolving hostnames to `10.0.0.1`, or
+    /// failing for `bad.example.com`, which does not exist, and
+    /// `down.example.com`, which times out
+    #[derive(Debug, Default)]
+    struct CountingResolver {
+        queries: usize,
+        ttl: Option<Duration>,
+    }
+
+    impl ResolveHostname for CountingResolver {
+        type Error = String;
+
+        fn resolve_hostname(&mut self, hostname: &str) -> Result<SocketAddr, String> {
+            self.resolve_hostname_with_ttl(hostname)
+                .map(|(addr, _)| addr)
+        }
+
+        fn resolve_hostname_with_ttl(
+            &mut self,
+            hostname: &str,
+        ) -> Result<(SocketAddr, Option<Duration>), String> {
+            self.queries += 1;
+            match hostname {
+                "bad.example.com" => Err("no such host".to_owned()),
+                "down.example.com" => Err("timed out".to_owned()),
+                _ => Ok((addr(1), self.ttl)),
+            }
+        }
+
+        fn is_negative_answer(error: &String) -> bool {
+            error == "no such host"
+        }
+    }
+
+    fn addr(last: u8) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, last)), 0)
+    }
+
+    #[test]
+    fn answers_are_cached_until_their_ttl_expires() {
+        let mut resolver = CachingResolver::new(CountingResolver {
+            ttl: Some(Duration::from_millis(20)),
+            ..Default::default()
+        });
+
+        assert_eq!(resolver.resolve_hostname("example.com"), Ok(addr(1)));
+        assert_eq!(resolver.resolve_hostname("EXAMPLE.com"), Ok(addr(1)));
+        assert!(resolver.resolve_hostname("bad.example.com").is_err());
+        assert!(resolver.resolve_hostname("bad.example.com").is_err());
+        assert_eq!(resolver.resolver().queries, 2);
+
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert_eq!(resolver.resolve_hostname("example.com"), Ok(addr(1)));
+        assert_eq!(resolver.resolver().queries, 3);
+    }
+
+    #[test]
+    fn only_negative_answers_are_cached() {
+        let mut resolver = CachingResolver::new(CountingResolver::default());
+
+        assert!(resolver.resolve_hostname("bad.example.com").is_err());
+        assert!(resolver.resolve_hostname("bad.example.com").is_err());
+        assert_eq!(resolver.resolver().queries, 1);
+
+        assert!(resolver.resolve_hostname("down.example.com").is_err());
+        assert!(resolver.resolve_hostname("down.example.com").is_err());
+        assert_eq!(resolver.resolver().queries, 3);
+        assert_eq!(resolver.len(), 1);
+    }
+
+    #[test]
+    fn least_recently_used_answer_is_evicted() {
+        let mut resolver = CachingResolver::with_capacity(CountingResolver::default(), 2);
+
+        resolver.resolve_hostname("a.example.com").unwrap();
+        resolver.resolve_hostname("b.example.com").unwrap();
+        resolver.resolve_hostname("a.example.com").unwrap();
+        resolver.resolve_hostname("c.example.com").unwrap();
+        assert_eq!(resolver.len(), 2);
+        assert_eq!(resolver.resolver().queries, 3);
+
+        resolver.resolve_hostname("a.example.com").unwrap();
+        assert_eq!(resolver.resolver().queries, 3);
+        resolver.resolve_hostname("b.example.com").unwrap();
+        assert_eq!(resolver.resolver().queries, 4);
+    }
+
+    #[test]
+    fn seeded_answers_are_used_until_flushed() {
+        let mut resolver = CachingResolver::new(CountingResolver::default());
+
+        resolver.seed("psp.example.com", addr(2), Duration::from_secs(60));
+        assert_eq!(resolver.resolve_hostname("psp.example.com"), Ok(addr(2)));
+        assert_eq!(resolver.resolver().queries, 0);
+
+        resolver.flush();
+        assert!(resolver.is_empty());
+        assert_eq!(resolver.resolve_hostname("psp.example.com"), Ok(addr(1)));
+        assert_eq!(resolver.resolver().queries, 1);
+    }
+}
@@ -0,0 +1,401 @@
+//! A minimal single-threaded async executor
+//!
+//! The [`Executor`] runs the futures of this crate's async APIs (the
+//! [`embedded_io_async`] implementations of the sockets, and
+//! [`AsyncTlsSocket`](crate::socket::tls::AsyncTlsSocket)) side by side on the
+//! current thread. Its reactor puts the thread to sleep until a socket a task
+//! waits for becomes ready, or a [`sleep`] expires.
+//!
+//! # Example
+//! ```ignore
+//! use core::time::Duration;
+//! use psp_net::executor::{sleep, Executor};
+//!
+//! let mut executor = Executor::new();
+//! executor.spawn(async move {
+//!     let mut buf = [0u8; 64];
+//!     let n = udp_socket.read(&mut buf).await.unwrap();
+//!     // ...
+//! });
+//!
+//! executor.block_on(async {
+//!     tls_socket.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+//!     sleep(Duration::from_millis(100)).await;
+//! });
+//! ```
+//!
+//! # Notes
+//! - The reactor waits in `sceNetInetPoll`, like
+//!   [`Poller`](crate::socket::poll::Poller), until a socket tasks wait for is
+//!   ready or the nearest [`sleep`] expires. Wakers used from other threads
+//!   interrupt the wait through a loopback UDP socket. Time is measured with
+//!   the RTC ticks.
+//! - The reactor is handed to the tasks through their [`Context`](core::task::Context). Futures of
+//!   this crate polled by another executor still work, but are woken as soon
+//!   as they return [`Poll::Pending`].
+
+use alloc::{boxed::Box, rc::Rc, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    cell::RefCell,
+    fmt::{self, Debug},
+    future::Future,
+    pin::{pin, Pin},
+    sync::atomic::{AtomicBool, Ordering},
+    task::{ContextBuilder, Poll, Waker},
+    time::Duration,
+};
+
+mod reactor;
+
+pub(crate) use self::reactor::wait_for;
+pub use self::reactor::Sleep;
+use self::reactor::{Notifier, Reactor};
+
+/// A spawned task
+struct Task {
+    /// The future of the task
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    /// Whether the task was woken since it was last polled
+    flag: Arc<WakeFlag>,
+    /// Wakes the task
+    waker: Waker,
+}
+
+impl Task {
+    /// Create a new task, woken so that it is polled right away
+    fn new(future: impl Future<Output = ()> + 'static, notifier: &Arc<Notifier>) -> Self {
+        let flag = Arc::new(WakeFlag::new(notifier.clone()));
+        Self {
+            future: Box::pin(future),
+            waker: Waker::from(flag.clone()),
+            flag,
+        }
+    }
+}
+
+/// Records that a task was woken, and wakes the reactor
+#[derive(Debug)]
+struct WakeFlag {
+    /// Whether the task was woken since it was last polled
+    woken: AtomicBool,
+    /// Wakes the reactor of the executor
+    notifier: Arc<Notifier>,
+}
+
+impl WakeFlag {
+    /// Create a flag, set so that the task is polled right away
+    fn new(notifier: Arc<Notifier>) -> Self {
+        Self {
+            woken: AtomicBool::new(true),
+            notifier,
+        }
+    }
+
+    /// Whether the task was woken, clearing the flag
+    fn take(&self) -> bool {
+        self.woken.swap(false, Ordering::AcqRel)
+    }
+
+    /// Whether the task was woken
+    fn is_set(&self) -> bool {
+        self.woken.load(Ordering::Acquire)
+    }
+}
+
+impl Wake for WakeFlag {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // the reactor may wait: the first wake interrupts it
+        if !self.woken.swap(true, Ordering::AcqRel) {
+            self.notifier.notify();
+        }
+    }
+}
+
+/// Spawns tasks on an [`Executor`]
+///
+/// Unlike the executor, it can be moved into the tasks, so that they can
+/// spawn further tasks. Get one with [`Executor::spawner`].
+#[derive(Clone, Default)]
+pub struct Spawner {
+    /// Tasks spawned since the executor last ran
+    queue: Rc<RefCell<Vec<Task>>>,
+    /// Wakes the reactor of the executor
+    notifier: Arc<Notifier>,
+}
+
+impl Spawner {
+    /// Spawn a task
+    ///
+    /// The task runs the next time the executor runs, until it completes.
+    ///
+    /// # Parameters
+    /// - `future`: The future the task runs
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.queue
+            .borrow_mut()
+            .push(Task::new(future, &self.notifier));
+        self.notifier.notify();
+    }
+}
+
+impl Debug for Spawner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spawner")
+            .field("queued", &self.queue.borrow().len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A single-threaded async executor
+///
+/// Tasks are [spawned](Executor::spawn), then run while the executor
+/// [blocks on](Executor::block_on) a future, or [runs](Executor::run) until
+/// they all complete. Tasks still running when [`Executor::block_on`] returns
+/// are resumed the next time the executor runs.
+pub struct Executor {
+    /// The running tasks
+    tasks: Vec<Task>,
+    /// Spawns tasks on this executor
+    spawner: Spawner,
+    /// Wakes the tasks waiting for sockets and timers
+    reactor: Reactor,
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        let reactor = Reactor::new();
+        Self {
+            tasks: Vec::new(),
+            spawner: Spawner {
+                queue: Rc::default(),
+                notifier: reactor.notifier(),
+            },
+            reactor,
+        }
+    }
+}
+
+impl Executor {
+    /// Create a new executor, with no tasks
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a [`Spawner`] for this executor
+    #[must_use]
+    pub fn spawner(&self) -> Spawner {
+        self.spawner.clone()
+    }
+
+    /// Spawn a task
+    ///
+    /// The task runs the next time the executor runs, until it completes.
+    ///
+    /// # Parameters
+    /// - `future`: The future the task runs
+    pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
+        self.spawner.spawn(future);
+    }
+
+    /// Run `future` to completion, running the spawned tasks meanwhile
+    ///
+    /// # Returns
+    /// The output of `future`
+    pub fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        let flag = Arc::new(WakeFlag::new(self.reactor.notifier()));
+        let waker = Waker::from(flag.clone());
+
+        loop {
+            if flag.take() {
+                self.reactor.forget(&waker);
+                let mut cx = ContextBuilder::from_waker(&waker)
+                    .ext(&mut self.reactor)
+                    .build();
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    self.reactor.forget(&waker);
+                    return output;
+                }
+            }
+
+            self.poll_tasks();
+            if !flag.is_set() && !self.has_woken_tasks() {
+                self.reactor.turn();
+            }
+        }
+    }
+
+    /// Run the spawned tasks until they all complete
+    pub fn run(&mut self) {
+        loop {
+            self.poll_tasks();
+            if self.tasks.is_empty() {
+                return;
+            }
+            if !self.has_woken_tasks() {
+                self.reactor.turn();
+            }
+        }
+    }
+
+    /// Poll the woken tasks once, dropping the completed ones
+    fn poll_tasks(&mut self) {
+        self.tasks.append(&mut self.spawner.queue.borrow_mut());
+
+        let reactor = &mut self.reactor;
+        self.tasks.retain_mut(|task| {
+            if !task.flag.take() {
+                return true;
+            }
+            reactor.forget(&task.waker);
+            let mut cx = ContextBuilder::from_waker(&task.waker)
+                .ext(&mut *reactor)
+                .build();
+            task.future.as_mut().poll(&mut cx).is_pending()
+        });
+    }
+
+    /// Whether a task was woken, or spawned, since it was last polled
+    fn has_woken_tasks(&self) -> bool {
+        !self.spawner.queue.borrow().is_empty() || self.tasks.iter().any(|task| task.flag.is_set())
+    }
+}
+
+impl Debug for Executor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Executor")
+            .field("tasks", &self.tasks.len())
+            .field("spawner", &self.spawner)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Run a future to completion on the current thread
+///
+/// A shorthand for [`Executor::block_on`] on a new executor. Use an
+/// [`Executor`] directly to spawn tasks.
+///
+/// # Returns
+/// The output of `future`
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    Executor::new().block_on(future)
+}
+
+/// Wait until `duration` has elapsed, without blocking the other tasks
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep::new(duration)
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use alloc::vec;
+    use std::io::Write;
+
+    use embedded_io_async::Read;
+
+    use crate::{
+        backend::{DefaultBackend, NetBackend},
+        socket::{
+            poll::{Interest, Pollable},
+            tcp::TcpSocket,
+            udp::UdpSocket,
+            SocketAddr,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn tasks_run_side_by_side() {
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let mut executor = Executor::new();
+
+        for (id, millis) in [(1, 30), (2, 10)] {
+            let order = order.clone();
+            executor.spawn(async move {
+                sleep(Duration::from_millis(millis)).await;
+                order.borrow_mut().push(id);
+            });
+        }
+        let spawner = executor.spawner();
+        let nested = order.clone();
+        executor.spawn(async move {
+            spawner.spawn(async move { nested.borrow_mut().push(3) });
+        });
+
+        let start = DefaultBackend::now_micros();
+        let output = executor.block_on(async {
+            sleep(Duration::from_millis(20)).await;
+            order.borrow_mut().push(0);
+            42
+        });
+        assert_eq!(output, 42);
+        assert_eq!(*order.borrow(), vec![3, 2, 0]);
+
+        executor.run();
+        assert_eq!(*order.borrow(), vec![3, 2, 0, 1]);
+        assert!(DefaultBackend::now_micros() - start >= 30_000);
+    }
+
+    #[test]
+    fn socket_readiness_wakes_task() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut socket = TcpSocket::new()
+            .unwrap()
+            .connect(listener.local_addr().unwrap())
+            .unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let mut executor = Executor::new();
+        let task_received = received.clone();
+        executor.spawn(async move {
+            let mut buf = [0u8; 5];
+            Read::read_exact(&mut socket, &mut buf).await.unwrap();
+            task_received.borrow_mut().extend_from_slice(&buf);
+        });
+
+        executor.block_on(async {
+            sleep(Duration::from_millis(20)).await;
+            peer.write_all(b"ready").unwrap();
+        });
+        executor.run();
+        assert_eq!(*received.borrow(), b"ready");
+    }
+
+    #[test]
+    fn block_on_notices_wakes_from_other_threads() {
+        // never readable: only the other thread completes the future
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap();
+        let done = Arc::new(AtomicBool::new(false));
+
+        let mut executor = Executor::new();
+        let mut waiting = None;
+        executor.block_on(core::future::poll_fn(|cx| {
+            if done.load(Ordering::Acquire) {
+                return Poll::Ready(());
+            }
+            wait_for(cx, socket.socket_fd(), Interest::READABLE);
+            waiting.get_or_insert_with(|| {
+                let (waker, done) = (cx.waker().clone(), done.clone());
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    done.store(true, Ordering::Release);
+                    waker.wake();
+                })
+            });
+            Poll::Pending
+        }));
+
+        // the registration of the completed future is gone
+        assert_eq!(executor.reactor.len(), 0);
+    }
+}
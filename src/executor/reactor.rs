@@ -0,0 +1,365 @@
+use alloc::{
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use crate::{
+    backend::{DefaultBackend, NetBackend},
+    netc::pollfd,
+    socket::{
+        poll::{self, Interest, Pollable},
+        sce::{RawSocketFileDescriptor, SocketFileDescriptor},
+        udp::UdpSocket,
+        SocketAddr, SocketAddrV4, ToSockaddr,
+    },
+};
+
+/// Time [`Reactor::turn`] waits for at most, when other threads cannot wake it
+/// (see [`Notifier`])
+const UNNOTIFIED_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Wakes a [`Reactor`] waiting in [`Reactor::turn`], from any thread
+///
+/// It sends a datagram to a loopback socket the reactor polls along with the
+/// sockets tasks wait for.
+#[derive(Debug, Default)]
+pub(crate) struct Notifier {
+    /// The non-blocking socket sending the datagrams, and the address of the
+    /// reactor's
+    sender: Option<(SocketFileDescriptor, SocketAddrV4)>,
+}
+
+impl Notifier {
+    /// Wake the reactor, if it waits
+    ///
+    /// Otherwise, its next turn returns right away.
+    pub(crate) fn notify(&self) {
+        if let Some((fd, addr)) = &self.sender {
+            // failures are ignored: a full buffer already wakes the reactor
+            DefaultBackend::send_to(**fd, &[0], 0, &addr.to_sockaddr());
+        }
+    }
+}
+
+/// The non-blocking socket a [`Notifier`] wakes the reactor through, and the
+/// notifier
+///
+/// # Returns
+/// `None` if the loopback sockets could not be created
+fn notification_socket() -> Option<(SocketFileDescriptor, Notifier)> {
+    let loopback = SocketAddr::from(([127, 0, 0, 1], 0));
+    let receiver = UdpSocket::new().ok()?.bind(Some(loopback)).ok()?;
+    let sender = UdpSocket::new().ok()?.bind(Some(loopback)).ok()?;
+    receiver.set_nonblocking(true).ok()?;
+    sender.set_nonblocking(true).ok()?;
+    let SocketAddr::V4(addr) = receiver.local_addr().ok()? else {
+        return None;
+    };
+    Some((
+        receiver.socket_fd().clone(),
+        Notifier {
+            sender: Some((sender.socket_fd().clone(), addr)),
+        },
+    ))
+}
+
+/// A task waiting for a socket to be ready
+#[derive(Debug)]
+struct Waiter {
+    /// The socket. It is not kept open by the reactor.
+    fd: Weak<RawSocketFileDescriptor>,
+    /// The readiness the task waits for
+    interest: Interest,
+    /// Wakes the task
+    waker: Waker,
+}
+
+/// A task waiting for a deadline
+#[derive(Debug)]
+struct Timer {
+    /// The deadline, in microseconds (see [`NetBackend::now_micros`])
+    deadline: u64,
+    /// Wakes the task
+    waker: Waker,
+}
+
+/// The network reactor of an [`Executor`](super::Executor)
+///
+/// Futures register the sockets and deadlines they wait for, and
+/// [`Reactor::turn`] wakes them once they are ready. Registrations are one-shot.
+#[derive(Debug)]
+pub(crate) struct Reactor {
+    /// Tasks waiting for a socket
+    waiters: Vec<Waiter>,
+    /// Tasks waiting for a deadline
+    timers: Vec<Timer>,
+    /// The socket the [`Notifier`] sends to, if it could be created
+    notifications: Option<SocketFileDescriptor>,
+    /// Wakes the reactor from any thread
+    notifier: Arc<Notifier>,
+}
+
+impl Reactor {
+    /// Create a new reactor, with no registrations
+    pub(crate) fn new() -> Self {
+        let (notifications, notifier) = notification_socket().unzip();
+        Self {
+            waiters: Vec::new(),
+            timers: Vec::new(),
+            notifications,
+            notifier: Arc::new(notifier.unwrap_or_default()),
+        }
+    }
+
+    /// The notifier waking the reactor, for the wakers of its tasks
+    pub(crate) fn notifier(&self) -> Arc<Notifier> {
+        self.notifier.clone()
+    }
+    /// Wake the task of `waker` once the socket is ready for `interest`
+    fn register(&mut self, fd: &SocketFileDescriptor, interest: Interest, waker: &Waker) {
+        self.waiters.push(Waiter {
+            fd: Arc::downgrade(&fd.0),
+            interest,
+            waker: waker.clone(),
+        });
+    }
+
+    /// Wake the task of `waker` once `deadline` is reached
+    fn add_timer(&mut self, deadline: u64, waker: &Waker) {
+        self.timers.push(Timer {
+            deadline,
+            waker: waker.clone(),
+        });
+    }
+
+    /// Remove the registrations of the task of `waker`
+    ///
+    /// Called before the task is polled: the poll registers again what the
+    /// task still waits for, so that the registrations of dropped futures do
+    /// not linger.
+    pub(crate) fn forget(&mut self, waker: &Waker) {
+        self.waiters.retain(|waiter| !waiter.waker.will_wake(waker));
+        self.timers.retain(|timer| !timer.waker.will_wake(waker));
+    }
+
+    /// Wait until a registered socket is ready, a deadline is reached, or the
+    /// [`Notifier`] is notified, and wake the tasks waiting for them
+    ///
+    /// The thread sleeps in the poll of the backend, until the nearest
+    /// deadline, or indefinitely without deadlines.
+    pub(crate) fn turn(&mut self) {
+        if self.wake_expired() {
+            return;
+        }
+
+        // the sockets are kept open while polled, and closed ones are ready
+        let sockets: Vec<Option<SocketFileDescriptor>> = self
+            .waiters
+            .iter()
+            .map(|waiter| waiter.fd.upgrade().map(SocketFileDescriptor))
+            .collect();
+        let mut fds: Vec<pollfd> = self
+            .notifications
+            .iter()
+            .map(|fd| poll::to_pollfd(fd, Interest::READABLE))
+            .chain(self.waiters.iter().zip(&sockets).map(|(waiter, fd)| {
+                fd.as_ref()
+                    .map_or_else(pollfd::default, |fd| poll::to_pollfd(fd, waiter.interest))
+            }))
+            .collect();
+
+        let timeout = if sockets.iter().any(Option::is_none) {
+            Some(Duration::ZERO)
+        } else {
+            self.timeout()
+        };
+        let polled = poll::poll_fds(&mut fds, timeout).is_ok();
+
+        if let Some(fd) = &self.notifications {
+            let mut buf = [0u8; 16];
+            while DefaultBackend::recv(**fd, &mut buf, 0) >= 0 {}
+        }
+
+        // sockets that could not be polled are ready: the task then gets the
+        // outcome from the operation it retries
+        let offset = fds.len() - sockets.len();
+        let ready: Vec<bool> = self
+            .waiters
+            .iter()
+            .zip(&sockets)
+            .zip(&fds[offset..])
+            .map(|((waiter, fd), pollfd)| {
+                let Some(fd) = fd.as_ref().filter(|_| polled) else {
+                    return true;
+                };
+                poll::readiness(fd, waiter.interest, pollfd.revents)
+                    .map_or(true, |(readiness, _)| !readiness.is_empty())
+            })
+            .collect();
+        let mut ready = ready.into_iter();
+        self.waiters.retain(|waiter| {
+            let ready = ready.next().unwrap_or(true);
+            if ready {
+                waiter.waker.wake_by_ref();
+            }
+            !ready
+        });
+
+        self.wake_expired();
+    }
+
+    /// Time to wait for in [`Reactor::turn`]: until the nearest deadline, or
+    /// indefinitely without deadlines
+    fn timeout(&self) -> Option<Duration> {
+        let now = DefaultBackend::now_micros();
+        let timeout = self
+            .timers
+            .iter()
+            .map(|timer| Duration::from_micros(timer.deadline.saturating_sub(now)))
+            .min();
+        if self.notifications.is_some() {
+            timeout
+        } else {
+            // other threads cannot interrupt the wait
+            Some(timeout.map_or(UNNOTIFIED_POLL_INTERVAL, |timeout| {
+                timeout.min(UNNOTIFIED_POLL_INTERVAL)
+            }))
+        }
+    }
+
+    /// Wake the tasks whose deadline is reached
+    ///
+    /// # Returns
+    /// Whether a task was woken
+    fn wake_expired(&mut self) -> bool {
+        let now = DefaultBackend::now_micros();
+        let mut woken = false;
+
+        self.timers.retain(|timer| {
+            let expired = timer.deadline <= now;
+            if expired {
+                timer.waker.wake_by_ref();
+                woken = true;
+            }
+            !expired
+        });
+
+        woken
+    }
+
+    /// The number of registered sockets and deadlines
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.waiters.len() + self.timers.len()
+    }
+}
+
+/// Get the reactor of the executor polling the current task, if any
+fn current<'a>(cx: &'a mut Context<'_>) -> Option<&'a mut Reactor> {
+    cx.ext().downcast_mut::<Reactor>()
+}
+
+/// Wake the current task once the socket is ready for `interest`
+///
+/// Outside of an [`Executor`](super::Executor), the task is woken right away,
+/// so that its executor polls it again after running its other tasks.
+pub(crate) fn wait_for(cx: &mut Context<'_>, fd: &SocketFileDescriptor, interest: Interest) {
+    let waker = cx.waker().clone();
+    match current(cx) {
+        Some(reactor) => reactor.register(fd, interest, &waker),
+        None => waker.wake(),
+    }
+}
+
+/// A future that completes once a duration has elapsed
+///
+/// Created by [`sleep`](super::sleep).
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct Sleep {
+    /// The deadline, in microseconds (see [`NetBackend::now_micros`])
+    deadline: u64,
+}
+
+impl Sleep {
+    /// Create a future that completes once `duration` has elapsed
+    pub(crate) fn new(duration: Duration) -> Self {
+        let duration = u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        Self {
+            deadline: DefaultBackend::now_micros().saturating_add(duration),
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if DefaultBackend::now_micros() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let waker = cx.waker().clone();
+        match current(cx) {
+            Some(reactor) => reactor.add_timer(self.deadline, &waker),
+            None => waker.wake(),
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use alloc::task::Wake;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Counts the wakes of a task
+    #[derive(Debug, Default)]
+    struct WakeCounter(AtomicUsize);
+
+    impl Wake for WakeCounter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn turn_sleeps_until_a_deadline_or_a_notification() {
+        // never readable
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap();
+        let counter = Arc::new(WakeCounter::default());
+        let waker = Waker::from(counter.clone());
+        let mut reactor = Reactor::new();
+        reactor.register(socket.socket_fd(), Interest::READABLE, &waker);
+
+        let start = DefaultBackend::now_micros();
+        reactor.add_timer(start + 30_000, &waker);
+        reactor.turn();
+        assert!(DefaultBackend::now_micros() - start >= 30_000);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(reactor.len(), 1);
+
+        // without deadlines, the wait only ends once notified
+        let notifier = reactor.notifier();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(30));
+            notifier.notify();
+        });
+        let start = DefaultBackend::now_micros();
+        reactor.turn();
+        assert!(DefaultBackend::now_micros() - start >= 30_000);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(reactor.len(), 1);
+    }
+}
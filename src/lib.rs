@@ -5,21 +5,28 @@
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_possible_wrap)]
 #![feature(slice_pattern)]
+#![cfg_attr(feature = "async", feature(context_ext, local_waker))]
 
 extern crate alloc;
+#[cfg(all(feature = "host", not(feature = "psp")))]
+extern crate std;
 
+#[cfg(any(feature = "psp", feature = "host"))]
+pub mod backend;
 pub mod constants;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub mod dns;
+#[cfg(all(feature = "async", any(feature = "psp", feature = "host")))]
+pub mod executor;
 #[cfg(feature = "http")]
 pub mod http;
 #[cfg(feature = "macros")]
 pub mod macros;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub mod netc;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub mod socket;
 pub mod traits;
 pub mod types;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub mod utils;
@@ -1,5 +1,8 @@
 #[allow(unused)]
 pub const AF_INET: u8 = 2;
+/// Unspecified address family, used to dissolve a UDP association
+#[allow(unused)]
+pub const AF_UNSPEC: u8 = 0;
 /// Stream socket (TCP)
 #[allow(unused)]
 pub const SOCK_STREAM: i32 = 1;
@@ -7,10 +10,232 @@ pub const SOCK_STREAM: i32 = 1;
 #[allow(unused)]
 pub const SOCK_DGRAM: i32 = 2;
 
+/// Shut down the receiving half of a connection
+#[allow(unused)]
+pub const SHUT_RD: i32 = 0;
+/// Shut down the sending half of a connection
+#[allow(unused)]
+pub const SHUT_WR: i32 = 1;
+/// Shut down both halves of a connection
+#[allow(unused)]
+pub const SHUT_RDWR: i32 = 2;
+
+/// Socket-level options
+#[allow(unused)]
+pub const SOL_SOCKET: i32 = 0xffff;
+/// Allow local address reuse
+#[allow(unused)]
+pub const SO_REUSEADDR: i32 = 0x0004;
+/// Keep connections alive
+#[allow(unused)]
+pub const SO_KEEPALIVE: i32 = 0x0008;
+/// Permit sending of broadcast messages
+#[allow(unused)]
+pub const SO_BROADCAST: i32 = 0x0020;
+/// Linger on close if data present
+#[allow(unused)]
+pub const SO_LINGER: i32 = 0x0080;
+/// Send buffer size
+#[allow(unused)]
+pub const SO_SNDBUF: i32 = 0x1001;
+/// Receive buffer size
+#[allow(unused)]
+pub const SO_RCVBUF: i32 = 0x1002;
+/// Send timeout
+#[allow(unused)]
+pub const SO_SNDTIMEO: i32 = 0x1005;
+/// Receive timeout
+#[allow(unused)]
+pub const SO_RCVTIMEO: i32 = 0x1006;
+/// Get error status and clear
+#[allow(unused)]
+pub const SO_ERROR: i32 = 0x1007;
+/// Get socket type
+#[allow(unused)]
+pub const SO_TYPE: i32 = 0x1008;
+/// Non-blocking I/O (PSP specific)
+#[allow(unused)]
+pub const SO_NONBLOCK: i32 = 0x1009;
+
+/// TCP protocol level
+#[allow(unused)]
+pub const IPPROTO_TCP: i32 = 6;
+/// Don't delay send to coalesce packets
+#[allow(unused)]
+pub const TCP_NODELAY: i32 = 0x01;
+
+/// IP protocol level
+#[allow(unused)]
+pub const IPPROTO_IP: i32 = 0;
+/// Time to live of outgoing multicast datagrams
+#[allow(unused)]
+pub const IP_MULTICAST_TTL: i32 = 10;
+/// Loop outgoing multicast datagrams back to the local host
+#[allow(unused)]
+pub const IP_MULTICAST_LOOP: i32 = 11;
+/// Join a multicast group
+#[allow(unused)]
+pub const IP_ADD_MEMBERSHIP: i32 = 12;
+/// Leave a multicast group
+#[allow(unused)]
+pub const IP_DROP_MEMBERSHIP: i32 = 13;
+
+/// Data can be read without blocking
+#[allow(unused)]
+pub const POLLIN: i16 = 0x0001;
+/// Urgent data can be read without blocking
+#[allow(unused)]
+pub const POLLPRI: i16 = 0x0002;
+/// Data can be written without blocking
+#[allow(unused)]
+pub const POLLOUT: i16 = 0x0004;
+/// An error is pending (only reported in `revents`)
+#[allow(unused)]
+pub const POLLERR: i16 = 0x0008;
+/// The connection was closed (only reported in `revents`)
+#[allow(unused)]
+pub const POLLHUP: i16 = 0x0010;
+/// The file descriptor is not open (only reported in `revents`)
+#[allow(unused)]
+pub const POLLNVAL: i16 = 0x0020;
+
+/// Interrupted system call
+#[allow(unused)]
+pub const EINTR: i32 = 4;
+/// Input/output error
+#[allow(unused)]
+pub const EIO: i32 = 5;
+/// Bad file descriptor
+#[allow(unused)]
+pub const EBADF: i32 = 9;
+/// Resource temporarily unavailable
+#[allow(unused)]
+pub const EAGAIN: i32 = 11;
+/// Operation would block
+#[allow(unused)]
+pub const EWOULDBLOCK: i32 = EAGAIN;
+/// Not enough memory
+#[allow(unused)]
+pub const ENOMEM: i32 = 12;
+/// Invalid argument
+#[allow(unused)]
+pub const EINVAL: i32 = 22;
+/// Broken pipe
+#[allow(unused)]
+pub const EPIPE: i32 = 32;
+/// Connection reset by peer
+#[allow(unused)]
+pub const ECONNRESET: i32 = 104;
+/// No buffer space available
+#[allow(unused)]
+pub const ENOBUFS: i32 = 105;
+/// Address family not supported by protocol
+#[allow(unused)]
+pub const EAFNOSUPPORT: i32 = 106;
+/// Connection refused
+#[allow(unused)]
+pub const ECONNREFUSED: i32 = 111;
+/// Address already in use
+#[allow(unused)]
+pub const EADDRINUSE: i32 = 112;
+/// Software caused connection abort
+#[allow(unused)]
+pub const ECONNABORTED: i32 = 113;
+/// Network is unreachable
+#[allow(unused)]
+pub const ENETUNREACH: i32 = 114;
+/// Connection timed out
+#[allow(unused)]
+pub const ETIMEDOUT: i32 = 116;
+/// Host is unreachable
+#[allow(unused)]
+pub const EHOSTUNREACH: i32 = 118;
+/// Operation now in progress
+#[allow(unused)]
+pub const EINPROGRESS: i32 = 119;
+/// Message too long
+#[allow(unused)]
+pub const EMSGSIZE: i32 = 122;
+/// Can't assign requested address
+#[allow(unused)]
+pub const EADDRNOTAVAIL: i32 = 125;
+/// Socket is already connected
+#[allow(unused)]
+pub const EISCONN: i32 = 127;
+/// Socket is not connected
+#[allow(unused)]
+pub const ENOTCONN: i32 = 128;
+
+#[cfg(feature = "psp")]
 pub use psp::sys::in_addr;
 
+#[cfg(feature = "psp")]
 pub use psp::sys::sockaddr;
 
+/// Length of a socket address
+#[cfg(feature = "psp")]
+pub use psp::sys::socklen_t;
+
+/// An IPv4 address, laid out like the PSP's `in_addr`
+#[cfg(not(feature = "psp"))]
+#[repr(C)]
+#[allow(nonstandard_style)]
+pub struct in_addr(pub u32);
+
+/// A generic socket address, laid out like the PSP's `sockaddr`
+#[cfg(not(feature = "psp"))]
+#[repr(C)]
+#[allow(nonstandard_style)]
+#[derive(Clone, Copy, Debug)]
+pub struct sockaddr {
+    pub sa_len: u8,
+    pub sa_family: u8,
+    pub sa_data: [u8; 14],
+}
+
+/// Length of a socket address
+#[cfg(not(feature = "psp"))]
+#[allow(nonstandard_style)]
+pub type socklen_t = u32;
+
+/// A structure like Linux's `linger`, used by the `SO_LINGER` option
+#[repr(C)]
+#[allow(nonstandard_style)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct linger {
+    /// Whether lingering is enabled
+    pub l_onoff: i32,
+    /// Linger time, in seconds
+    pub l_linger: i32,
+}
+
+/// A structure like BSD's `ip_mreq`, used by the `IP_ADD_MEMBERSHIP` and
+/// `IP_DROP_MEMBERSHIP` options
+///
+/// Addresses are in network byte order.
+#[repr(C)]
+#[allow(nonstandard_style)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ip_mreq {
+    /// Address of the multicast group
+    pub imr_multiaddr: u32,
+    /// Address of the local interface to join the group on
+    pub imr_interface: u32,
+}
+
+/// A structure like BSD's `pollfd`, used by `sceNetInetPoll`
+#[repr(C)]
+#[allow(nonstandard_style)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct pollfd {
+    /// The file descriptor of the socket
+    pub fd: i32,
+    /// The events waited for (`POLLIN`, `POLLOUT`, ...)
+    pub events: i16,
+    /// The events that occurred, set by the poll
+    pub revents: i16,
+}
+
 /// A structure like Linux's `sockaddr_in`
 #[repr(C)]
 #[allow(nonstandard_style)]
@@ -0,0 +1,258 @@
+//! LAN peer discovery
+//!
+//! [`discover`] finds peers on the local network: it periodically broadcasts an
+//! announcement from a bound [`UdpSocket`], and collects the replies until a
+//! timeout expires. Peers answer the announcement with
+//! [`UdpSocket::send_to`], to the address they received it from.
+//!
+//! # Example
+//! ```ignore
+//! use core::{net::Ipv4Addr, time::Duration};
+//! use psp_net::socket::{discovery::{discover, DiscoveryOptions}, udp::broadcast_address};
+//!
+//! let socket = UdpSocket::new()?.bind(None)?;
+//! let target = broadcast_address(console_ip, netmask);
+//! let mut options = DiscoveryOptions::new(SocketAddr::from((target, GAME_PORT)));
+//! options.set_timeout(Duration::from_secs(3));
+//!
+//! for peer in discover(&socket, b"GAME?", &options)? {
+//!     // peer.addr() answered with peer.reply()
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use core::{net::Ipv4Addr, time::Duration};
+
+use crate::{
+    backend::{DefaultBackend, NetBackend},
+    traits::SocketBuffer,
+};
+
+use super::{
+    error::SocketError,
+    poll::{self, Interest, Pollable},
+    state::Bound,
+    udp::UdpSocket,
+    SocketAddr,
+};
+
+/// Default time between two announcements
+pub const DEFAULT_ANNOUNCE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Default time [`discover`] collects replies for
+pub const DEFAULT_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest reply [`discover`] receives. Longer replies are truncated.
+pub const MAX_REPLY_LEN: usize = 512;
+
+/// Options of a [`discover`] run
+///
+/// # Fields
+/// - [`target`](Self::target): Where announcements are sent
+/// - [`interval`](Self::interval): Time between two announcements
+/// - [`timeout`](Self::timeout): Time replies are collected for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DiscoveryOptions {
+    /// Where announcements are sent
+    target: SocketAddr,
+    /// Time between two announcements
+    interval: Duration,
+    /// Time replies are collected for
+    timeout: Duration,
+}
+
+impl DiscoveryOptions {
+    /// Create new discovery options
+    ///
+    /// By default, announcements are sent every [`DEFAULT_ANNOUNCE_INTERVAL`],
+    /// for [`DEFAULT_DISCOVERY_TIMEOUT`].
+    ///
+    /// # Arguments
+    /// - `target`: Where announcements are sent, usually a broadcast address
+    ///   (see [`broadcast_address`](super::udp::broadcast_address))
+    #[must_use]
+    pub fn new(target: SocketAddr) -> Self {
+        Self {
+            target,
+            interval: DEFAULT_ANNOUNCE_INTERVAL,
+            timeout: DEFAULT_DISCOVERY_TIMEOUT,
+        }
+    }
+
+    /// Create new discovery options targeting every host of the local network
+    ///
+    /// Announcements are sent to the limited broadcast address
+    /// (`255.255.255.255`) on `port`.
+    #[must_use]
+    pub fn local_network(port: u16) -> Self {
+        Self::new(SocketAddr::from((Ipv4Addr::BROADCAST, port)))
+    }
+
+    /// Get where announcements are sent
+    #[must_use]
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Get the time between two announcements
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Set the time between two announcements
+    ///
+    /// # Arguments
+    /// - `interval`: The time between two announcements
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Get the time replies are collected for
+    #[must_use]
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Set the time replies are collected for
+    ///
+    /// # Arguments
+    /// - `timeout`: The time replies are collected for
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+}
+
+/// A peer found by [`discover`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Peer {
+    /// The address the peer replied from
+    addr: SocketAddr,
+    /// The first reply of the peer
+    reply: Vec<u8>,
+}
+
+impl Peer {
+    /// The address the peer replied from
+    #[must_use]
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The first reply of the peer
+    #[must_use]
+    pub fn reply(&self) -> &[u8] {
+        &self.reply
+    }
+}
+
+/// Discover peers on the local network
+///
+/// Broadcasting is enabled on `socket`, then `announcement` is sent to the
+/// target of `options` every interval, until the timeout expires. Every peer
+/// that replied in the meantime is returned once, in the order it first replied.
+///
+/// # Notes
+/// Datagrams equal to `announcement` are ignored, so that the announcements
+/// looped back to `socket` are not mistaken for replies. Peers must
+/// therefore reply with a different payload.
+///
+/// # Errors
+/// - [`SocketError::UnsupportedAddressFamily`] if the target is not an IPv4 address
+/// - Any [`SocketError`] if broadcasting could not be enabled, or an
+///   announcement could not be sent or a reply received
+pub fn discover<B: SocketBuffer>(
+    socket: &UdpSocket<Bound, B>,
+    announcement: &[u8],
+    options: &DiscoveryOptions,
+) -> Result<Vec<Peer>, SocketError> {
+    socket.set_broadcast(true)?;
+
+    let start = DefaultBackend::now_micros();
+    let timeout = micros(options.timeout);
+    let interval = micros(options.interval).max(1);
+    let mut next_announcement = 0;
+
+    let mut peers: Vec<Peer> = Vec::new();
+    let mut buf = [0u8; MAX_REPLY_LEN];
+    loop {
+        let elapsed = DefaultBackend::now_micros().saturating_sub(start);
+        if elapsed >= timeout {
+            return Ok(peers);
+        }
+        if elapsed >= next_announcement {
+            socket.send_to(announcement, options.target)?;
+            next_announcement = elapsed.saturating_add(interval);
+        }
+
+        let wait = next_announcement.min(timeout) - elapsed;
+        let (readiness, _) = poll::wait(
+            socket.socket_fd(),
+            Interest::READABLE,
+            Some(Duration::from_micros(wait)),
+        )?;
+        if readiness.is_empty() {
+            continue;
+        }
+
+        let (len, from) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(SocketError::WouldBlock) => continue,
+            Err(error) => return Err(error),
+        };
+        let reply = &buf[..len];
+        if reply != announcement && peers.iter().all(|peer| peer.addr != from) {
+            peers.push(Peer {
+                addr: from,
+                reply: reply.to_vec(),
+            });
+        }
+    }
+}
+
+/// Convert a duration to microseconds, saturating
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discover_collects_unique_peers() {
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap();
+
+        // a reply waiting before the first announcement is collected too
+        let early = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        early
+            .send_to(b"early", socket.local_addr().unwrap())
+            .unwrap();
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer_addr = peer.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 16];
+            while let Ok((n, from)) = peer.recv_from(&mut buf) {
+                // echo the announcement, as a broadcast looped back would
+                peer.send_to(&buf[..n], from).unwrap();
+                peer.send_to(b"here", from).unwrap();
+            }
+        });
+
+        let mut options = DiscoveryOptions::new(peer_addr);
+        options.set_interval(Duration::from_millis(20));
+        options.set_timeout(Duration::from_millis(200));
+
+        let found = discover(&socket, b"anyone?", &options).unwrap();
+        assert!(socket.broadcast().unwrap());
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].addr(), early.local_addr().unwrap());
+        assert_eq!(found[0].reply(), b"early");
+        assert_eq!(found[1].addr(), peer_addr);
+        assert_eq!(found[1].reply(), b"here");
+    }
+}
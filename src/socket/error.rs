@@ -1,6 +1,8 @@
 use alloc::string::String;
 use thiserror::Error;
 
+use super::super::netc;
+
 /// An error that can occur with a socket
 #[derive(Debug, Clone, PartialEq, Eq, Default, Error)]
 pub enum SocketError {
@@ -10,6 +12,57 @@ pub enum SocketError {
     /// Socket error with errno
     #[error("Errno: {0}")]
     Errno(i32),
+    /// The operation would block.
+    ///
+    /// Returned by non-blocking sockets when the operation cannot be
+    /// completed immediately (`EAGAIN`/`EWOULDBLOCK`).
+    #[error("Operation would block")]
+    WouldBlock,
+    /// The operation timed out (`ETIMEDOUT`)
+    #[error("Operation timed out")]
+    TimedOut,
+    /// The remote host refused the connection (`ECONNREFUSED`)
+    #[error("Connection refused")]
+    ConnectionRefused,
+    /// The connection was reset by the remote host (`ECONNRESET`)
+    #[error("Connection reset by peer")]
+    ConnectionReset,
+    /// The connection was aborted by the local network stack (`ECONNABORTED`)
+    #[error("Connection aborted")]
+    ConnectionAborted,
+    /// The socket is not connected (`ENOTCONN`)
+    #[error("Socket is not connected")]
+    NotConnected,
+    /// The socket is already connected (`EISCONN`)
+    #[error("Socket is already connected")]
+    AlreadyConnected,
+    /// The local address is already in use (`EADDRINUSE`)
+    #[error("Address already in use")]
+    AddrInUse,
+    /// The requested local address is not available (`EADDRNOTAVAIL`)
+    #[error("Address not available")]
+    AddrNotAvailable,
+    /// The remote host is unreachable (`EHOSTUNREACH`)
+    #[error("Host is unreachable")]
+    HostUnreachable,
+    /// The network is unreachable (`ENETUNREACH`)
+    #[error("Network is unreachable")]
+    NetworkUnreachable,
+    /// Writing is not possible anymore, e.g. the connection was shut down (`EPIPE`)
+    #[error("Broken pipe")]
+    BrokenPipe,
+    /// The operation was interrupted (`EINTR`)
+    #[error("Operation interrupted")]
+    Interrupted,
+    /// An argument passed to the network stack was invalid (`EINVAL`)
+    #[error("Invalid argument")]
+    InvalidArgument,
+    /// The datagram is larger than the network stack can send at once (`EMSGSIZE`)
+    #[error("Message too long")]
+    MessageTooLong,
+    /// The network stack ran out of memory (`ENOMEM`/`ENOBUFS`)
+    #[error("Out of memory")]
+    OutOfMemory,
     /// Socket error with errno and a description
     #[error("Errno: {0} ({1})")]
     ErrnoWithDescription(i32, String),
@@ -31,13 +84,108 @@ impl SocketError {
     {
         SocketError::ErrnoWithDescription(errno, description.into())
     }
+
+    /// Create a [`SocketError`] from an errno value
+    ///
+    /// Errno values having a dedicated variant are mapped to it, all the
+    /// others are wrapped in [`SocketError::Errno`].
+    #[must_use]
+    pub fn from_errno(errno: i32) -> Self {
+        match errno {
+            netc::EAGAIN => SocketError::WouldBlock,
+            netc::ETIMEDOUT => SocketError::TimedOut,
+            netc::ECONNREFUSED => SocketError::ConnectionRefused,
+            netc::ECONNRESET => SocketError::ConnectionReset,
+            netc::ECONNABORTED => SocketError::ConnectionAborted,
+            netc::ENOTCONN => SocketError::NotConnected,
+            netc::EISCONN => SocketError::AlreadyConnected,
+            netc::EADDRINUSE => SocketError::AddrInUse,
+            netc::EADDRNOTAVAIL => SocketError::AddrNotAvailable,
+            netc::EHOSTUNREACH => SocketError::HostUnreachable,
+            netc::ENETUNREACH => SocketError::NetworkUnreachable,
+            netc::EPIPE => SocketError::BrokenPipe,
+            netc::EINTR => SocketError::Interrupted,
+            netc::EINVAL => SocketError::InvalidArgument,
+            netc::EMSGSIZE => SocketError::MessageTooLong,
+            netc::ENOMEM | netc::ENOBUFS => SocketError::OutOfMemory,
+            _ => SocketError::Errno(errno),
+        }
+    }
+
+    /// The errno value this error corresponds to, if any
+    #[must_use]
+    pub fn errno(&self) -> Option<i32> {
+        match self {
+            SocketError::Errno(errno) | SocketError::ErrnoWithDescription(errno, _) => Some(*errno),
+            SocketError::WouldBlock => Some(netc::EAGAIN),
+            SocketError::TimedOut => Some(netc::ETIMEDOUT),
+            SocketError::ConnectionRefused => Some(netc::ECONNREFUSED),
+            SocketError::ConnectionReset => Some(netc::ECONNRESET),
+            SocketError::ConnectionAborted => Some(netc::ECONNABORTED),
+            SocketError::NotConnected => Some(netc::ENOTCONN),
+            SocketError::AlreadyConnected => Some(netc::EISCONN),
+            SocketError::AddrInUse => Some(netc::EADDRINUSE),
+            SocketError::AddrNotAvailable => Some(netc::EADDRNOTAVAIL),
+            SocketError::HostUnreachable => Some(netc::EHOSTUNREACH),
+            SocketError::NetworkUnreachable => Some(netc::ENETUNREACH),
+            SocketError::BrokenPipe => Some(netc::EPIPE),
+            SocketError::Interrupted => Some(netc::EINTR),
+            SocketError::InvalidArgument => Some(netc::EINVAL),
+            SocketError::MessageTooLong => Some(netc::EMSGSIZE),
+            SocketError::OutOfMemory => Some(netc::ENOMEM),
+            SocketError::UnsupportedAddressFamily
+            | SocketError::Other(_)
+            | SocketError::Unknown => None,
+        }
+    }
+
+    /// Returns `true` if the error is [`SocketError::WouldBlock`].
+    ///
+    /// # Notes
+    /// `embedded-io` 0.6 (used by `embedded-tls`) has no `WouldBlock` error kind,
+    /// so [`embedded_io::Error::kind`] cannot tell this error apart. Use this
+    /// method instead when using non-blocking sockets.
+    #[must_use]
+    pub fn is_would_block(&self) -> bool {
+        matches!(self, Self::WouldBlock)
+    }
 }
 
 impl embedded_io::Error for SocketError {
+    /// The kind of the error
+    ///
+    /// # Notes
+    /// `embedded-io` 0.6 has no kind for [`SocketError::WouldBlock`],
+    /// [`SocketError::HostUnreachable`] and [`SocketError::NetworkUnreachable`],
+    /// which are therefore reported as [`embedded_io::ErrorKind::Other`].
     fn kind(&self) -> embedded_io::ErrorKind {
+        use embedded_io::ErrorKind;
+
         match self {
-            SocketError::UnsupportedAddressFamily => embedded_io::ErrorKind::Unsupported,
-            _ => embedded_io::ErrorKind::Other,
+            SocketError::UnsupportedAddressFamily => ErrorKind::Unsupported,
+            SocketError::TimedOut => ErrorKind::TimedOut,
+            SocketError::ConnectionRefused => ErrorKind::ConnectionRefused,
+            SocketError::ConnectionReset => ErrorKind::ConnectionReset,
+            SocketError::ConnectionAborted => ErrorKind::ConnectionAborted,
+            SocketError::NotConnected => ErrorKind::NotConnected,
+            SocketError::AlreadyConnected => ErrorKind::AlreadyExists,
+            SocketError::AddrInUse => ErrorKind::AddrInUse,
+            SocketError::AddrNotAvailable => ErrorKind::AddrNotAvailable,
+            SocketError::BrokenPipe => ErrorKind::BrokenPipe,
+            SocketError::Interrupted => ErrorKind::Interrupted,
+            SocketError::InvalidArgument | SocketError::MessageTooLong => ErrorKind::InvalidInput,
+            SocketError::OutOfMemory => ErrorKind::OutOfMemory,
+            SocketError::Errno(errno) | SocketError::ErrnoWithDescription(errno, _) => {
+                match SocketError::from_errno(*errno) {
+                    SocketError::Errno(_) => ErrorKind::Other,
+                    decoded => decoded.kind(),
+                }
+            }
+            SocketError::WouldBlock
+            | SocketError::HostUnreachable
+            | SocketError::NetworkUnreachable
+            | SocketError::Other(_)
+            | SocketError::Unknown => ErrorKind::Other,
         }
     }
 }
@@ -91,3 +239,123 @@ impl embedded_io::Error for TlsSocketError {
 
 // re-exports
 pub type TlsError = embedded_tls::TlsError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_io::{Error, ErrorKind};
+
+    #[test]
+    fn errno_variants_and_kinds() {
+        let cases = [
+            (netc::EAGAIN, SocketError::WouldBlock, ErrorKind::Other),
+            (netc::ETIMEDOUT, SocketError::TimedOut, ErrorKind::TimedOut),
+            (
+                netc::ECONNREFUSED,
+                SocketError::ConnectionRefused,
+                ErrorKind::ConnectionRefused,
+            ),
+            (
+                netc::ECONNRESET,
+                SocketError::ConnectionReset,
+                ErrorKind::ConnectionReset,
+            ),
+            (
+                netc::ECONNABORTED,
+                SocketError::ConnectionAborted,
+                ErrorKind::ConnectionAborted,
+            ),
+            (
+                netc::ENOTCONN,
+                SocketError::NotConnected,
+                ErrorKind::NotConnected,
+            ),
+            (
+                netc::EISCONN,
+                SocketError::AlreadyConnected,
+                ErrorKind::AlreadyExists,
+            ),
+            (
+                netc::EADDRINUSE,
+                SocketError::AddrInUse,
+                ErrorKind::AddrInUse,
+            ),
+            (
+                netc::EADDRNOTAVAIL,
+                SocketError::AddrNotAvailable,
+                ErrorKind::AddrNotAvailable,
+            ),
+            (
+                netc::EHOSTUNREACH,
+                SocketError::HostUnreachable,
+                ErrorKind::Other,
+            ),
+            (
+                netc::ENETUNREACH,
+                SocketError::NetworkUnreachable,
+                ErrorKind::Other,
+            ),
+            (netc::EPIPE, SocketError::BrokenPipe, ErrorKind::BrokenPipe),
+            (
+                netc::EINTR,
+                SocketError::Interrupted,
+                ErrorKind::Interrupted,
+            ),
+            (
+                netc::EINVAL,
+                SocketError::InvalidArgument,
+                ErrorKind::InvalidInput,
+            ),
+            (
+                netc::EMSGSIZE,
+                SocketError::MessageTooLong,
+                ErrorKind::InvalidInput,
+            ),
+            (
+                netc::ENOMEM,
+                SocketError::OutOfMemory,
+                ErrorKind::OutOfMemory,
+            ),
+        ];
+
+        for (errno, error, kind) in cases {
+            assert_eq!(SocketError::from_errno(errno), error);
+            assert_eq!(error.errno(), Some(errno), "{error}");
+            assert_eq!(error.kind(), kind, "{error}");
+            assert_eq!(error.is_would_block(), errno == netc::EAGAIN, "{error}");
+            // wrapped errno values are decoded
+            assert_eq!(SocketError::Errno(errno).kind(), kind, "{error}");
+        }
+    }
+
+    #[test]
+    fn unmapped_errors() {
+        assert_eq!(
+            SocketError::from_errno(netc::ENOBUFS),
+            SocketError::OutOfMemory
+        );
+        assert_eq!(
+            SocketError::from_errno(netc::EBADF),
+            SocketError::Errno(netc::EBADF)
+        );
+
+        let cases = [
+            (SocketError::Errno(netc::EBADF), Some(netc::EBADF)),
+            (
+                SocketError::new_errno_with_description(netc::EBADF, "closed"),
+                Some(netc::EBADF),
+            ),
+            (SocketError::Other("other".into()), None),
+            (SocketError::Unknown, None),
+        ];
+        for (error, errno) in cases {
+            assert_eq!(error.errno(), errno, "{error}");
+            assert_eq!(error.kind(), ErrorKind::Other, "{error}");
+            assert!(!error.is_would_block(), "{error}");
+        }
+        assert_eq!(
+            SocketError::UnsupportedAddressFamily.kind(),
+            ErrorKind::Unsupported
+        );
+    }
+}
@@ -2,11 +2,8 @@
 /// Get the current timestamp
 macro_rules! timestamp {
     () => {{
-        let mut seed = 0;
-        unsafe {
-            psp::sys::sceRtcGetCurrentTick(&mut seed);
-        }
-        seed
+        use $crate::backend::NetBackend;
+        $crate::backend::DefaultBackend::now_micros()
     }};
 }
 
@@ -34,12 +31,17 @@ macro_rules! timestamp {
 ///
 /// # Example
 /// ```no_run
+/// # extern crate alloc;
+/// # use psp_net::tls_socket;
+/// # fn main() -> Result<(), psp_net::socket::error::TlsSocketError> {
 /// tls_socket! {
 ///     result: _maybe_socket,
 ///     host "myhost.com" => "1.2.3.4",
 /// }
 /// let mut tls_socket = _maybe_socket?;
 /// tls_socket.write_all("hello world".as_bytes());
+/// # Ok(())
+/// # }
 /// ```
 macro_rules! tls_socket {
     (
@@ -166,20 +168,28 @@ macro_rules! tls_socket {
 ///
 /// The macro supports the following syntaxes:
 /// ```no_run
+/// # use embedded_io::Read;
+/// # use psp_net::{read, socket::{state::Ready, tls::TlsSocket}};
+/// # fn example(socket: &mut TlsSocket<'_, Ready>) {
+/// # let mut buf = [0; 1024];
 /// // syntax 1
 /// read!(from socket);
 /// // syntax 2
 /// read!(from socket => buf);
 /// // syntax 3
 /// read!(string from socket);
+/// # }
 /// ```
 ///
 /// # Example
 /// Read a string from the socket
 /// ```no_run
+/// # use psp_net::{read, socket::{state::Ready, tls::TlsSocket}};
+/// # fn example(socket: &mut TlsSocket<'_, Ready>) {
 /// if let Ok(s) = read!(string from socket) {
 /// println!("{}", s);
 /// }
+/// # }
 /// ```
 #[macro_export]
 macro_rules! read {
@@ -201,7 +211,13 @@ macro_rules! read {
 ///
 /// # Example
 /// ```no_run
+/// # #![feature(slice_pattern)]
+/// # use psp_net::{socket::{state::Ready, tls::TlsSocket}, write};
+/// # fn example(socket: &mut TlsSocket<'_, Ready>) -> Result<(), embedded_tls::TlsError> {
+/// # let buf = b"hello world".to_vec();
 /// write!(buf => socket)?;
+/// # Ok(())
+/// # }
 /// ```
 #[macro_export]
 macro_rules! write {
@@ -1,29 +1,43 @@
 //! This module contains the socket types.
 //! Currently, three socket types are provided:
 //! - [`udp::UdpSocket`] – a UDP socket
-//! - [`tcp::TcpSocket`] – a TCP socket
+//! - [`tcp::TcpSocket`] – a TCP socket, which can also act as a server
+//!   ([`tcp::TcpListener`])
 //! - [`tls::TlsSocket`] – a TCP socket wrapper that provides a TLS connection
+//!
+//! The [`discovery`] module builds upon UDP broadcasts to find peers on the
+//! local network.
 
 #![allow(clippy::module_name_repetitions)]
 
 use core::net::Ipv4Addr;
-use psp::sys::{in_addr, sockaddr};
 
-use super::netc;
+use super::netc::{self, in_addr, sockaddr};
 
+pub mod discovery;
 pub mod error;
 #[cfg(feature = "macros")]
 pub mod macros;
-mod sce;
+pub mod option;
+pub mod poll;
+pub(crate) mod sce;
 pub mod state;
 pub mod tcp;
 pub mod tls;
 pub mod udp;
 
+/// Convert an [`Ipv4Addr`] to a number in network order
+///
+/// The bytes of the number in memory are the octets of the address, as
+/// expected by the `sin_addr` of a [`sockaddr`] and the fields of an
+/// [`netc::ip_mreq`].
+pub(crate) fn ipv4_to_network_order(ip: Ipv4Addr) -> u32 {
+    u32::from_ne_bytes(ip.octets())
+}
+
 /// Convert a [`SocketAddrV4`] to a [`sockaddr`]
 fn socket_addr_v4_to_sockaddr(addr: SocketAddrV4) -> sockaddr {
-    let octets = addr.ip().octets();
-    let sin_addr = u32::from_le_bytes(octets);
+    let sin_addr = ipv4_to_network_order(*addr.ip());
     let port = addr.port().to_be();
 
     let sockaddr_in = netc::sockaddr_in {
@@ -37,6 +51,28 @@ fn socket_addr_v4_to_sockaddr(addr: SocketAddrV4) -> sockaddr {
     unsafe { core::mem::transmute::<netc::sockaddr_in, netc::sockaddr>(sockaddr_in) }
 }
 
+/// Which halves of a connection to shut down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Shutdown {
+    /// No more data can be received
+    Read,
+    /// No more data can be sent. The peer receives an end of stream.
+    Write,
+    /// No more data can be sent nor received
+    Both,
+}
+
+impl Shutdown {
+    /// The `how` argument of the shutdown call
+    pub(crate) fn how(self) -> i32 {
+        match self {
+            Shutdown::Read => netc::SHUT_RD,
+            Shutdown::Write => netc::SHUT_WR,
+            Shutdown::Both => netc::SHUT_RDWR,
+        }
+    }
+}
+
 /// Convert to a [`sockaddr`]
 pub trait ToSockaddr {
     /// Convert to a [`sockaddr`]
@@ -55,6 +91,9 @@ pub trait ToSocketAddr {
     fn to_socket_addr(&self) -> SocketAddr;
 }
 
+/// The [`in_addr`] holds the address as a number, the first octet being the
+/// most significant byte, as returned by the [DNS resolver](crate::dns). This
+/// differs from the `sin_addr` of a [`sockaddr`], which is in network order.
 impl ToSocketAddr for in_addr {
     fn to_socket_addr(&self) -> SocketAddr {
         let octets = self.0.to_be_bytes();
@@ -68,7 +107,7 @@ impl ToSocketAddr for sockaddr {
         let sockaddr_in =
             unsafe { core::mem::transmute::<netc::sockaddr, netc::sockaddr_in>(*self) };
 
-        let octets = sockaddr_in.sin_addr.0.to_be_bytes();
+        let octets = sockaddr_in.sin_addr.0.to_ne_bytes();
 
         let port = u16::to_be(sockaddr_in.sin_port);
 
@@ -82,3 +121,31 @@ impl ToSocketAddr for sockaddr {
 // re-exports
 pub type SocketAddr = core::net::SocketAddr;
 pub type SocketAddrV4 = core::net::SocketAddrV4;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sockaddr_round_trip() {
+        let addr = SocketAddrV4::new(Ipv4Addr::new(192, 168, 1, 42), 8080);
+        let sockaddr = addr.to_sockaddr();
+
+        let sockaddr_in =
+            unsafe { core::mem::transmute::<netc::sockaddr, netc::sockaddr_in>(sockaddr) };
+        assert_eq!(sockaddr_in.sin_addr.0.to_ne_bytes(), [192, 168, 1, 42]);
+        assert_eq!(sockaddr_in.sin_port.to_ne_bytes(), 8080u16.to_be_bytes());
+
+        assert_eq!(sockaddr.to_socket_addr(), SocketAddr::V4(addr));
+    }
+
+    #[test]
+    fn membership_addresses_are_in_network_order() {
+        use option::{AddMembership, SetSocketOption};
+
+        let group = Ipv4Addr::new(239, 1, 2, 3);
+        let mreq = AddMembership::to_raw((group, Ipv4Addr::new(192, 168, 1, 42)));
+        assert_eq!(mreq.imr_multiaddr.to_ne_bytes(), [239, 1, 2, 3]);
+        assert_eq!(mreq.imr_interface.to_ne_bytes(), [192, 168, 1, 42]);
+    }
+}
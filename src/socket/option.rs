@@ -0,0 +1,409 @@
+//! Typed socket options
+//!
+//! Every supported option is a type implementing [`SocketOption`]. The type
+//! defines the level and name of the option, as well as the type of its value,
+//! so that passing a wrong value is a compile time error.
+//!
+//! Options are set and read through the `set_option` and `option` methods of
+//! [`TcpSocket`](super::tcp::TcpSocket) and [`UdpSocket`](super::udp::UdpSocket).
+//!
+//! # Example
+//! ```ignore
+//! use core::time::Duration;
+//! use psp_net::socket::option::{NoDelay, ReceiveTimeout};
+//!
+//! let socket = TcpSocket::new()?;
+//! socket.set_option::<ReceiveTimeout>(Some(Duration::from_secs(5)))?;
+//! socket.set_option::<NoDelay>(true)?;
+//! let no_delay = socket.option::<NoDelay>()?;
+//! ```
+
+use core::{net::Ipv4Addr, time::Duration};
+
+use super::super::netc;
+use super::{error::SocketError, ipv4_to_network_order};
+
+/// A socket option
+pub trait SocketOption {
+    /// The type of the option value
+    type Value;
+    /// The representation of the value passed to the network stack
+    type Raw: Copy + Default;
+    /// The level of the option (e.g. `SOL_SOCKET`)
+    const LEVEL: i32;
+    /// The name of the option (e.g. `SO_REUSEADDR`)
+    const NAME: i32;
+}
+
+/// A socket option that can be read
+pub trait GetSocketOption: SocketOption {
+    /// Convert the raw value returned by the network stack
+    fn from_raw(raw: Self::Raw) -> Self::Value;
+}
+
+/// A socket option that can be set
+pub trait SetSocketOption: SocketOption {
+    /// Convert the value into the representation expected by the network stack
+    fn to_raw(value: Self::Value) -> Self::Raw;
+}
+
+/// Implement a boolean socket option
+///
+/// The raw value is an `i32`, unless another integer type is given.
+macro_rules! bool_option {
+    ($(#[$attr:meta])* $name:ident, $level:expr, $opt:expr) => {
+        bool_option!($(#[$attr])* $name, $level, $opt, i32);
+    };
+    ($(#[$attr:meta])* $name:ident, $level:expr, $opt:expr, $raw:ty) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl SocketOption for $name {
+            type Value = bool;
+            type Raw = $raw;
+            const LEVEL: i32 = $level;
+            const NAME: i32 = $opt;
+        }
+
+        impl GetSocketOption for $name {
+            fn from_raw(raw: $raw) -> bool {
+                raw != 0
+            }
+        }
+
+        impl SetSocketOption for $name {
+            fn to_raw(value: bool) -> $raw {
+                <$raw>::from(value)
+            }
+        }
+    };
+}
+
+/// Implement a multicast group membership socket option
+macro_rules! membership_option {
+    ($(#[$attr:meta])* $name:ident, $opt:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl SocketOption for $name {
+            /// The address of the group, and of the local interface
+            type Value = (Ipv4Addr, Ipv4Addr);
+            type Raw = netc::ip_mreq;
+            const LEVEL: i32 = netc::IPPROTO_IP;
+            const NAME: i32 = $opt;
+        }
+
+        impl SetSocketOption for $name {
+            fn to_raw((group, interface): (Ipv4Addr, Ipv4Addr)) -> netc::ip_mreq {
+                netc::ip_mreq {
+                    imr_multiaddr: ipv4_to_network_order(group),
+                    imr_interface: ipv4_to_network_order(interface),
+                }
+            }
+        }
+    };
+}
+
+/// Implement a timeout socket option
+macro_rules! timeout_option {
+    ($(#[$attr:meta])* $name:ident, $opt:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl SocketOption for $name {
+            type Value = Option<Duration>;
+            type Raw = u32;
+            const LEVEL: i32 = netc::SOL_SOCKET;
+            const NAME: i32 = $opt;
+        }
+
+        impl GetSocketOption for $name {
+            fn from_raw(raw: u32) -> Option<Duration> {
+                (raw != 0).then(|| Duration::from_micros(u64::from(raw)))
+            }
+        }
+
+        impl SetSocketOption for $name {
+            fn to_raw(value: Option<Duration>) -> u32 {
+                // zero means "no timeout", so the shortest timeout is 1 microsecond
+                value.map_or(0, |timeout| {
+                    u32::try_from(timeout.as_micros()).unwrap_or(u32::MAX).max(1)
+                })
+            }
+        }
+    };
+}
+
+/// Implement a buffer size socket option
+macro_rules! size_option {
+    ($(#[$attr:meta])* $name:ident, $opt:expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl SocketOption for $name {
+            type Value = usize;
+            type Raw = i32;
+            const LEVEL: i32 = netc::SOL_SOCKET;
+            const NAME: i32 = $opt;
+        }
+
+        impl GetSocketOption for $name {
+            fn from_raw(raw: i32) -> usize {
+                raw.max(0) as usize
+            }
+        }
+
+        impl SetSocketOption for $name {
+            fn to_raw(value: usize) -> i32 {
+                i32::try_from(value).unwrap_or(i32::MAX)
+            }
+        }
+    };
+}
+
+timeout_option!(
+    /// Receive timeout (`SO_RCVTIMEO`)
+    ///
+    /// `None` means that receive calls block indefinitely. The timeout has
+    /// microsecond granularity.
+    ReceiveTimeout,
+    netc::SO_RCVTIMEO
+);
+
+timeout_option!(
+    /// Send timeout (`SO_SNDTIMEO`)
+    ///
+    /// `None` means that send calls block indefinitely. The timeout has
+    /// microsecond granularity.
+    SendTimeout,
+    netc::SO_SNDTIMEO
+);
+
+bool_option!(
+    /// Allow reuse of local addresses (`SO_REUSEADDR`)
+    ReuseAddress,
+    netc::SOL_SOCKET,
+    netc::SO_REUSEADDR
+);
+
+bool_option!(
+    /// Send keep-alive probes on idle connections (`SO_KEEPALIVE`)
+    KeepAlive,
+    netc::SOL_SOCKET,
+    netc::SO_KEEPALIVE
+);
+
+bool_option!(
+    /// Permit sending datagrams to broadcast addresses (`SO_BROADCAST`)
+    ///
+    /// Only meaningful for UDP sockets. See also `set_broadcast` on
+    /// [`UdpSocket`](super::udp::UdpSocket).
+    Broadcast,
+    netc::SOL_SOCKET,
+    netc::SO_BROADCAST
+);
+
+bool_option!(
+    /// Non-blocking mode (`SO_NONBLOCK`)
+    ///
+    /// See also `set_nonblocking` on the socket types.
+    NonBlocking,
+    netc::SOL_SOCKET,
+    netc::SO_NONBLOCK
+);
+
+bool_option!(
+    /// Disable Nagle's algorithm (`TCP_NODELAY`)
+    ///
+    /// Only meaningful for TCP sockets.
+    NoDelay,
+    netc::IPPROTO_TCP,
+    netc::TCP_NODELAY
+);
+
+bool_option!(
+    /// Loop outgoing multicast datagrams back to the local host (`IP_MULTICAST_LOOP`)
+    ///
+    /// Only meaningful for UDP sockets.
+    MulticastLoop,
+    netc::IPPROTO_IP,
+    netc::IP_MULTICAST_LOOP,
+    u8
+);
+
+membership_option!(
+    /// Join a multicast group (`IP_ADD_MEMBERSHIP`)
+    ///
+    /// The value is the address of the group, and of the local interface to
+    /// join it on ([`Ipv4Addr::UNSPECIFIED`] lets the network stack choose).
+    /// Only meaningful for UDP sockets. It cannot be read.
+    AddMembership,
+    netc::IP_ADD_MEMBERSHIP
+);
+
+membership_option!(
+    /// Leave a multicast group (`IP_DROP_MEMBERSHIP`)
+    ///
+    /// The value is the address of the group, and of the local interface it
+    /// was joined on. Only meaningful for UDP sockets. It cannot be read.
+    DropMembership,
+    netc::IP_DROP_MEMBERSHIP
+);
+
+/// Time to live of outgoing multicast datagrams (`IP_MULTICAST_TTL`)
+///
+/// The default of `1` keeps multicast datagrams in the local network.
+/// Values above `255` are clamped. Only meaningful for UDP sockets.
+#[derive(Debug, Clone, Copy)]
+pub struct MulticastTtl;
+
+impl SocketOption for MulticastTtl {
+    type Value = u32;
+    type Raw = u8;
+    const LEVEL: i32 = netc::IPPROTO_IP;
+    const NAME: i32 = netc::IP_MULTICAST_TTL;
+}
+
+impl GetSocketOption for MulticastTtl {
+    fn from_raw(raw: u8) -> u32 {
+        u32::from(raw)
+    }
+}
+
+impl SetSocketOption for MulticastTtl {
+    fn to_raw(value: u32) -> u8 {
+        u8::try_from(value).unwrap_or(u8::MAX)
+    }
+}
+
+size_option!(
+    /// Size of the receive buffer (`SO_RCVBUF`)
+    ReceiveBufferSize,
+    netc::SO_RCVBUF
+);
+
+size_option!(
+    /// Size of the send buffer (`SO_SNDBUF`)
+    SendBufferSize,
+    netc::SO_SNDBUF
+);
+
+/// Linger on close if unsent data is present (`SO_LINGER`)
+///
+/// `None` disables lingering. The linger time has second granularity.
+#[derive(Debug, Clone, Copy)]
+pub struct Linger;
+
+impl SocketOption for Linger {
+    type Value = Option<Duration>;
+    type Raw = netc::linger;
+    const LEVEL: i32 = netc::SOL_SOCKET;
+    const NAME: i32 = netc::SO_LINGER;
+}
+
+impl GetSocketOption for Linger {
+    fn from_raw(raw: netc::linger) -> Option<Duration> {
+        (raw.l_onoff != 0).then(|| Duration::from_secs(raw.l_linger.max(0) as u64))
+    }
+}
+
+impl SetSocketOption for Linger {
+    fn to_raw(value: Option<Duration>) -> netc::linger {
+        match value {
+            Some(linger) => netc::linger {
+                l_onoff: 1,
+                l_linger: i32::try_from(linger.as_secs()).unwrap_or(i32::MAX),
+            },
+            None => netc::linger::default(),
+        }
+    }
+}
+
+/// Pending socket error (`SO_ERROR`)
+///
+/// Reading this option clears the pending error. It cannot be set.
+#[derive(Debug, Clone, Copy)]
+pub struct PendingError;
+
+impl SocketOption for PendingError {
+    type Value = Option<SocketError>;
+    type Raw = i32;
+    const LEVEL: i32 = netc::SOL_SOCKET;
+    const NAME: i32 = netc::SO_ERROR;
+}
+
+impl GetSocketOption for PendingError {
+    fn from_raw(raw: i32) -> Option<SocketError> {
+        (raw != 0).then(|| SocketError::from_errno(raw))
+    }
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+    use crate::socket::{tcp::TcpSocket, udp::UdpSocket};
+
+    /// Set `$option` to each of the values, and read it back
+    macro_rules! assert_round_trip {
+        ($socket:expr, $option:ty, $($value:expr),+) => {
+            $(
+                $socket.set_option::<$option>($value).unwrap();
+                assert_eq!(
+                    $socket.option::<$option>().unwrap(),
+                    $value,
+                    stringify!($option)
+                );
+            )+
+        };
+    }
+
+    #[test]
+    fn bool_options_round_trip() {
+        let tcp = TcpSocket::new().unwrap();
+        assert_round_trip!(tcp, ReuseAddress, true, false);
+        assert_round_trip!(tcp, KeepAlive, true, false);
+        assert_round_trip!(tcp, NonBlocking, true, false);
+        assert_round_trip!(tcp, NoDelay, true, false);
+
+        let udp = UdpSocket::new().unwrap();
+        assert_round_trip!(udp, Broadcast, true, false);
+        assert_round_trip!(udp, MulticastLoop, false, true);
+    }
+
+    #[test]
+    fn timeout_options_round_trip() {
+        let tcp = TcpSocket::new().unwrap();
+        // a whole number of milliseconds, which the host keeps exactly
+        let timeout = Some(Duration::from_millis(1500));
+        assert_round_trip!(tcp, ReceiveTimeout, timeout, None);
+        assert_round_trip!(tcp, SendTimeout, timeout, None);
+    }
+
+    #[test]
+    fn size_options_round_trip() {
+        let tcp = TcpSocket::new().unwrap();
+        // the network stack may reserve more than asked for
+        tcp.set_option::<ReceiveBufferSize>(8192).unwrap();
+        assert!(tcp.option::<ReceiveBufferSize>().unwrap() >= 8192);
+        tcp.set_option::<SendBufferSize>(8192).unwrap();
+        assert!(tcp.option::<SendBufferSize>().unwrap() >= 8192);
+    }
+
+    #[test]
+    fn linger_round_trips() {
+        let tcp = TcpSocket::new().unwrap();
+        assert_round_trip!(tcp, Linger, Some(Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn multicast_ttl_round_trips() {
+        let udp = UdpSocket::new().unwrap();
+        assert_round_trip!(udp, MulticastTtl, 7, 1);
+        udp.set_option::<MulticastTtl>(300).unwrap();
+        assert_eq!(udp.option::<MulticastTtl>().unwrap(), 255);
+    }
+}
@@ -0,0 +1,411 @@
+//! Readiness polling across many sockets
+//!
+//! A [`Poller`] allows a single thread to wait on many sockets at once, instead
+//! of dedicating a thread to each blocking socket.
+//!
+//! # Example
+//! ```ignore
+//! use core::time::Duration;
+//! use psp_net::socket::poll::{Interest, Poller, Token};
+//!
+//! let mut poller = Poller::new();
+//! poller.register(&tcp_socket, Token(0), Interest::READABLE);
+//! poller.register(&udp_socket, Token(1), Interest::READABLE | Interest::WRITABLE);
+//!
+//! let mut events = Vec::new();
+//! poller.poll(&mut events, Some(Duration::from_millis(100)))?;
+//! for event in &events {
+//!     if event.token() == Token(0) && event.is_readable() {
+//!         // tcp_socket can be read without blocking
+//!     }
+//! }
+//! ```
+//!
+//! # Notes
+//! Readiness is waited for with the `poll` of the backend: `sceNetInetPoll` on
+//! the PSP, which the thread sleeps in until a socket is ready or the timeout
+//! expires.
+
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::time::Duration;
+
+use crate::backend::{DefaultBackend, NetBackend};
+
+use super::{
+    super::netc::{self, pollfd},
+    error::SocketError,
+    option::PendingError,
+    sce::SocketFileDescriptor,
+};
+
+bitflags! {
+    /// Readiness a socket is registered for, or has
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Hash)]
+    pub struct Interest: u8 {
+        /// The socket can be read without blocking
+        const READABLE = 0x1;
+        /// The socket can be written without blocking
+        const WRITABLE = 0x2;
+    }
+}
+
+/// Identifier of a socket registered in a [`Poller`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Token(pub usize);
+
+/// Types that can be registered in a [`Poller`]
+pub trait Pollable {
+    /// The file descriptor of the socket to poll
+    fn socket_fd(&self) -> &SocketFileDescriptor;
+}
+
+/// Readiness of a registered socket
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The token the socket was registered with
+    token: Token,
+    /// The readiness of the socket
+    readiness: Interest,
+    /// The error pending on the socket, if any
+    error: Option<SocketError>,
+}
+
+impl Event {
+    /// The token the socket was registered with
+    #[must_use]
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    /// The readiness of the socket
+    #[must_use]
+    pub fn readiness(&self) -> Interest {
+        self.readiness
+    }
+
+    /// Whether the socket can be read without blocking
+    #[must_use]
+    pub fn is_readable(&self) -> bool {
+        self.readiness.contains(Interest::READABLE)
+    }
+
+    /// Whether the socket can be written without blocking
+    #[must_use]
+    pub fn is_writable(&self) -> bool {
+        self.readiness.contains(Interest::WRITABLE)
+    }
+
+    /// The error pending on the socket, if any.
+    ///
+    /// This is set, for instance, when a non-blocking connect failed.
+    /// The error is cleared from the socket once reported.
+    #[must_use]
+    pub fn error(&self) -> Option<&SocketError> {
+        self.error.as_ref()
+    }
+}
+
+/// A registered socket
+#[derive(Debug, Clone)]
+struct Registration {
+    fd: SocketFileDescriptor,
+    token: Token,
+    interest: Interest,
+}
+
+/// A readiness poller
+///
+/// Sockets are registered with a [`Token`] identifying them, and the
+/// [`Interest`] they are polled for. [`Poller::poll`] then waits until at
+/// least one of them is ready, or the timeout expires.
+///
+/// # Notes
+/// The poller keeps a reference to the registered sockets' file descriptors,
+/// so they are not closed until they are [deregistered](Poller::deregister)
+/// or the poller is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Poller {
+    /// The registered sockets
+    registrations: Vec<Registration>,
+}
+
+impl Poller {
+    /// Create a new poller, with no registered sockets
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a socket
+    ///
+    /// If a socket is already registered with `token`, it is replaced.
+    ///
+    /// # Parameters
+    /// - `source`: The socket to register
+    /// - `token`: The token identifying the socket in the returned [`Event`]s
+    /// - `interest`: The readiness the socket is polled for
+    pub fn register<P: Pollable + ?Sized>(&mut self, source: &P, token: Token, interest: Interest) {
+        let registration = Registration {
+            fd: source.socket_fd().clone(),
+            token,
+            interest,
+        };
+        match self.registrations.iter_mut().find(|r| r.token == token) {
+            Some(existing) => *existing = registration,
+            None => self.registrations.push(registration),
+        }
+    }
+
+    /// Change the interest of a registered socket
+    ///
+    /// # Returns
+    /// `true` if a socket was registered with `token`, `false` otherwise
+    pub fn reregister(&mut self, token: Token, interest: Interest) -> bool {
+        match self.registrations.iter_mut().find(|r| r.token == token) {
+            Some(registration) => {
+                registration.interest = interest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deregister a socket
+    ///
+    /// # Returns
+    /// `true` if a socket was registered with `token`, `false` otherwise
+    pub fn deregister(&mut self, token: Token) -> bool {
+        let len = self.registrations.len();
+        self.registrations.retain(|r| r.token != token);
+        len != self.registrations.len()
+    }
+
+    /// Number of registered sockets
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.registrations.len()
+    }
+
+    /// Whether no socket is registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.registrations.is_empty()
+    }
+
+    /// Wait until at least one registered socket is ready
+    ///
+    /// # Parameters
+    /// - `events`: Cleared, then filled with an [`Event`] for each ready socket
+    /// - `timeout`: Maximum time to wait. `None` waits indefinitely, while a zero
+    ///   duration checks the sockets once without waiting.
+    ///
+    /// # Returns
+    /// The number of ready sockets, which is `0` if the timeout expired.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the sockets could not be polled
+    pub fn poll(
+        &self,
+        events: &mut Vec<Event>,
+        timeout: Option<Duration>,
+    ) -> Result<usize, SocketError> {
+        events.clear();
+        let mut fds: Vec<pollfd> = self
+            .registrations
+            .iter()
+            .map(|registration| to_pollfd(&registration.fd, registration.interest))
+            .collect();
+        poll_fds(&mut fds, timeout)?;
+
+        for (registration, fd) in self.registrations.iter().zip(&fds) {
+            let (readiness, error) =
+                readiness(&registration.fd, registration.interest, fd.revents)?;
+            if !readiness.is_empty() {
+                events.push(Event {
+                    token: registration.token,
+                    readiness,
+                    error,
+                });
+            }
+        }
+        Ok(events.len())
+    }
+}
+
+/// Wait until a single socket is ready, or the timeout expires
+///
+/// # Returns
+/// The readiness of the socket (empty if the timeout expired) and its
+/// pending error, if any.
+pub(crate) fn wait(
+    fd: &SocketFileDescriptor,
+    interest: Interest,
+    timeout: Option<Duration>,
+) -> Result<(Interest, Option<SocketError>), SocketError> {
+    let mut fds = [to_pollfd(fd, interest)];
+    poll_fds(&mut fds, timeout)?;
+    readiness(fd, interest, fds[0].revents)
+}
+
+/// Wait with the backend until at least one socket of `fds` is ready, or the
+/// timeout expires
+///
+/// `None` waits indefinitely. The wait is resumed if it is interrupted.
+///
+/// # Returns
+/// The number of ready sockets
+pub(crate) fn poll_fds(
+    fds: &mut [pollfd],
+    timeout: Option<Duration>,
+) -> Result<usize, SocketError> {
+    let deadline = timeout.map(|timeout| {
+        let timeout = u64::try_from(timeout.as_micros()).unwrap_or(u64::MAX);
+        DefaultBackend::now_micros().saturating_add(timeout)
+    });
+
+    loop {
+        // in milliseconds, rounded up so that the deadline is reached
+        let timeout = deadline.map_or(-1, |deadline| {
+            let left = deadline.saturating_sub(DefaultBackend::now_micros());
+            i32::try_from(left.div_ceil(1000)).unwrap_or(i32::MAX)
+        });
+        let result = DefaultBackend::poll(fds, timeout);
+        if result >= 0 {
+            return Ok(result as usize);
+        }
+        let errno = DefaultBackend::errno();
+        if errno != netc::EINTR {
+            return Err(SocketError::from_errno(errno));
+        }
+    }
+}
+
+/// Retry a non-blocking operation until it does not fail with [`SocketError::WouldBlock`]
+///
+/// While the operation would block, the task waits for the socket to be ready
+/// for `interest` (see [`executor`](crate::executor)).
+#[cfg(feature = "async")]
+pub(crate) async fn retry<T>(
+    fd: SocketFileDescriptor,
+    interest: Interest,
+    mut op: impl FnMut() -> Result<T, SocketError>,
+) -> Result<T, SocketError> {
+    core::future::poll_fn(|cx| match op() {
+        Err(SocketError::WouldBlock) => {
+            crate::executor::wait_for(cx, &fd, interest);
+            core::task::Poll::Pending
+        }
+        result => core::task::Poll::Ready(result),
+    })
+    .await
+}
+
+/// Check, without blocking, the readiness of a socket
+///
+/// # Returns
+/// The subset of `interest` the socket is ready for, and its pending error, if
+/// any. A socket with a pending error is reported as ready for any interest, so
+/// that the next operation returns the error.
+pub(crate) fn probe(
+    fd: &SocketFileDescriptor,
+    interest: Interest,
+) -> Result<(Interest, Option<SocketError>), SocketError> {
+    wait(fd, interest, Some(Duration::ZERO))
+}
+
+/// The entry polling `fd` for `interest`
+pub(crate) fn to_pollfd(fd: &SocketFileDescriptor, interest: Interest) -> pollfd {
+    let mut events = 0;
+    if interest.contains(Interest::READABLE) {
+        events |= netc::POLLIN;
+    }
+    if interest.contains(Interest::WRITABLE) {
+        events |= netc::POLLOUT;
+    }
+    pollfd {
+        fd: **fd,
+        events,
+        revents: 0,
+    }
+}
+
+/// The readiness of a socket polled for `interest`, from the events that
+/// occurred
+///
+/// # Returns
+/// See [`probe`].
+pub(crate) fn readiness(
+    fd: &SocketFileDescriptor,
+    interest: Interest,
+    revents: i16,
+) -> Result<(Interest, Option<SocketError>), SocketError> {
+    if revents & netc::POLLNVAL != 0 {
+        return Ok((interest, Some(SocketError::from_errno(netc::EBADF))));
+    }
+    if revents & netc::POLLERR != 0 {
+        // e.g. the connection failed
+        return Ok((interest, fd.option::<PendingError>()?));
+    }
+
+    let mut readiness = Interest::empty();
+    // once the peer closed the connection, a read returns the end of stream
+    if revents & (netc::POLLIN | netc::POLLHUP) != 0 {
+        readiness |= Interest::READABLE;
+    }
+    if revents & netc::POLLOUT != 0 {
+        readiness |= Interest::WRITABLE;
+    }
+    Ok((readiness & interest, None))
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use crate::socket::{state::Bound, udp::UdpSocket, SocketAddr};
+
+    use super::*;
+
+    fn bound_socket() -> UdpSocket<Bound> {
+        UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap()
+    }
+
+    #[test]
+    fn poll_reports_registered_sockets_until_deregistered() {
+        let (first, second) = (bound_socket(), bound_socket());
+        let mut poller = Poller::new();
+        poller.register(&first, Token(0), Interest::READABLE);
+        poller.register(&second, Token(1), Interest::READABLE);
+        assert_eq!(poller.len(), 2);
+
+        let mut events = Vec::new();
+        let start = DefaultBackend::now_micros();
+        assert_eq!(
+            poller.poll(&mut events, Some(Duration::from_millis(20))),
+            Ok(0)
+        );
+        assert!(DefaultBackend::now_micros() - start >= 20_000);
+
+        let to = second.local_addr().unwrap();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            peer.send_to(b"ready", to).unwrap();
+        });
+        assert_eq!(poller.poll(&mut events, None), Ok(1));
+        assert_eq!(events[0].token(), Token(1));
+        assert!(events[0].is_readable() && !events[0].is_writable());
+
+        assert!(poller.reregister(Token(1), Interest::WRITABLE));
+        assert_eq!(poller.poll(&mut events, Some(Duration::ZERO)), Ok(1));
+        assert_eq!(events[0].readiness(), Interest::WRITABLE);
+
+        assert!(poller.deregister(Token(1)));
+        assert!(!poller.deregister(Token(1)));
+        assert_eq!(poller.poll(&mut events, Some(Duration::ZERO)), Ok(0));
+        assert!(events.is_empty());
+        assert_eq!(poller.len(), 1);
+    }
+}
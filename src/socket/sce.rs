@@ -2,8 +2,14 @@
 
 use core::ops::Deref;
 
-use alloc::rc::Rc;
-use psp::sys;
+use alloc::sync::Arc;
+
+use crate::backend::{DefaultBackend, NetBackend};
+use crate::netc;
+
+use super::error::SocketError;
+use super::option::{GetSocketOption, SetSocketOption};
+use super::{Shutdown, SocketAddr, ToSocketAddr};
 
 /// Raw socket file descriptor
 ///
@@ -12,15 +18,14 @@ use psp::sys;
 ///
 /// # Notes
 /// The drop implementation of this type calls the close syscall.
-/// Closing via drop is best-effort as of now (errors are ignored).
+/// Closing via drop is best-effort (errors are ignored): use
+/// [`SocketFileDescriptor::close`] to observe them.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct RawSocketFileDescriptor(pub(crate) i32);
 
 impl Drop for RawSocketFileDescriptor {
     fn drop(&mut self) {
-        unsafe {
-            sys::sceNetInetClose(self.0);
-        };
+        DefaultBackend::close(self.0);
     }
 }
 
@@ -36,8 +41,12 @@ impl Deref for RawSocketFileDescriptor {
 ///
 /// This is a wrapper around a raw socket file descriptor, which
 /// takes care of closing it when no other references to it exist.
+///
+/// # Notes
+/// The handles are reference counted atomically, so that they can be shared
+/// between threads (see [`TcpSocket::into_split`](super::tcp::TcpSocket::into_split)).
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct SocketFileDescriptor(pub(crate) Rc<RawSocketFileDescriptor>);
+pub struct SocketFileDescriptor(pub(crate) Arc<RawSocketFileDescriptor>);
 
 impl SocketFileDescriptor {
     /// Create a new socket file descriptor.
@@ -48,7 +57,118 @@ impl SocketFileDescriptor {
     /// # Safety
     /// - `fd` must be a valid socket file descriptor
     pub(crate) fn new(fd: i32) -> Self {
-        Self(Rc::new(RawSocketFileDescriptor(fd)))
+        Self(Arc::new(RawSocketFileDescriptor(fd)))
+    }
+
+    /// Close the socket, reporting errors
+    ///
+    /// If other handles to the descriptor exist (e.g. it is registered in a
+    /// [`Poller`](super::poll::Poller)), only this handle is released: the socket
+    /// is closed, on drop, when the last handle is.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be closed
+    pub(crate) fn close(self) -> Result<(), SocketError> {
+        let Ok(fd) = self.into_raw() else {
+            return Ok(());
+        };
+
+        if DefaultBackend::close(fd) < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Release the raw file descriptor, which is then no longer closed on drop
+    ///
+    /// # Errors
+    /// The handle itself, if other handles to the descriptor exist
+    pub(crate) fn into_raw(self) -> Result<i32, Self> {
+        let raw = Arc::try_unwrap(self.0).map_err(Self)?;
+        let fd = raw.0;
+        core::mem::forget(raw);
+        Ok(fd)
+    }
+
+    /// Get the local address the socket is bound to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub(crate) fn local_addr(&self) -> Result<SocketAddr, SocketError> {
+        let mut sockaddr: netc::sockaddr = unsafe { core::mem::zeroed() };
+        if DefaultBackend::local_addr(**self, &mut sockaddr) < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(sockaddr.to_socket_addr())
+        }
+    }
+
+    /// Get the address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - [`SocketError::NotConnected`] if the socket is not connected
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub(crate) fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        let mut sockaddr: netc::sockaddr = unsafe { core::mem::zeroed() };
+        if DefaultBackend::peer_addr(**self, &mut sockaddr) < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(sockaddr.to_socket_addr())
+        }
+    }
+
+    /// Shut down part of a full-duplex connection
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the connection could not be shut down
+    pub(crate) fn shutdown(&self, how: Shutdown) -> Result<(), SocketError> {
+        if DefaultBackend::shutdown(**self, how.how()) < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Set a socket option
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be set
+    pub(crate) fn set_option<O: SetSocketOption>(
+        &self,
+        value: O::Value,
+    ) -> Result<(), SocketError> {
+        let raw = O::to_raw(value);
+        let bytes = unsafe {
+            core::slice::from_raw_parts((&raw const raw).cast::<u8>(), size_of::<O::Raw>())
+        };
+        if DefaultBackend::set_option(**self, O::LEVEL, O::NAME, bytes) < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Get a socket option
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be read
+    pub(crate) fn option<O: GetSocketOption>(&self) -> Result<O::Value, SocketError> {
+        let mut raw = O::Raw::default();
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut((&raw mut raw).cast::<u8>(), size_of::<O::Raw>())
+        };
+        if DefaultBackend::get_option(**self, O::LEVEL, O::NAME, bytes) < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(O::from_raw(raw))
+        }
     }
 }
 
@@ -11,26 +11,36 @@ use core::fmt::Debug;
 pub trait SocketState: Debug {}
 
 /// Socket is in an unbound state
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Unbound;
 impl SocketState for Unbound {}
 
 /// Socket is in a bound state
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Bound;
 impl SocketState for Bound {}
 
 /// Socket is in a connected state
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Connected;
 impl SocketState for Connected {}
 
 /// Socket is not ready to send or receive data
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NotReady;
 impl SocketState for NotReady {}
 
 /// Socket is ready to send or receive data
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Ready;
 impl SocketState for Ready {}
+
+/// Socket is listening for incoming connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Listening;
+impl SocketState for Listening {}
+
+/// Socket is connected, but can no longer send data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WriteShutdown;
+impl SocketState for WriteShutdown {}
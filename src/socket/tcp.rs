@@ -1,13 +1,13 @@
 #![allow(clippy::module_name_repetitions)]
 
 use alloc::vec::Vec;
-use embedded_io::{ErrorType, Read, Write};
+use embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
 
-use core::net::SocketAddr;
-use psp::sys;
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
 
-use core::ffi::c_void;
+use core::time::Duration;
 
+use crate::backend::{DefaultBackend, NetBackend};
 use crate::traits::io::{EasySocket, Open, OptionType};
 use crate::traits::SocketBuffer;
 use crate::types::{SocketOptions, SocketRecvFlags, SocketSendFlags};
@@ -15,9 +15,23 @@ use crate::types::{SocketOptions, SocketRecvFlags, SocketSendFlags};
 use super::super::netc;
 
 use super::error::SocketError;
+use super::option::{GetSocketOption, NonBlocking, SetSocketOption};
+use super::poll::{self, Interest, Pollable};
 use super::sce::SocketFileDescriptor;
-use super::state::{Connected, SocketState, Unbound};
-use super::ToSockaddr;
+use super::state::{Bound, Connected, Listening, SocketState, Unbound, WriteShutdown};
+use super::{Shutdown, ToSockaddr, ToSocketAddr};
+
+mod split;
+pub use split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, ReuniteError, WriteHalf};
+
+/// Default backlog used by [`TcpSocket::listen`] callers that do not need a
+/// specific value
+pub const DEFAULT_BACKLOG: i32 = 16;
+
+/// A TCP socket listening for incoming connections
+///
+/// See [`TcpSocket::bind`], [`TcpSocket::listen`] and [`TcpSocket::accept`].
+pub type TcpListener<B = Vec<u8>> = TcpSocket<Listening, B>;
 
 /// A TCP socket
 ///
@@ -27,6 +41,9 @@ use super::ToSockaddr;
 /// The socket is closed when the struct is dropped.
 /// Closing via drop is best-effort.
 ///
+/// The struct is not [`Clone`]: [`TcpSocket::try_clone`] creates another
+/// handle to the socket, sharing its file descriptor.
+///
 /// # Notes
 /// The structure implements [`EasySocket`]. This allows you to interact with
 /// the socket using a simplified API. However, you are still free to use it
@@ -34,17 +51,20 @@ use super::ToSockaddr;
 ///
 /// Using it as an easy socket allows you to use it in the following way:
 /// ```no_run
-/// use psp::net::TcpSocket;
+/// use psp_net::socket::tcp::TcpSocket;
+/// # use embedded_io::Write;
+/// # use psp_net::{socket::SocketAddr, traits::io::Open, types::SocketOptions};
+/// # let addr: SocketAddr = "1.2.3.4:80".parse().unwrap();
 ///
 /// let socket = TcpSocket::new().unwrap();
-/// let socket_options = SocketOptions{ remote: addr };
-/// let socket = socket.open(socket_options).unwrap();
+/// let socket_options = SocketOptions::new(addr);
+/// let mut socket = socket.open(&socket_options).unwrap();
 /// socket.write(b"hello world").unwrap();
 /// socket.flush().unwrap();
 /// // no need to call close, as drop will do it
 /// ```
 #[repr(C)]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash)]
 pub struct TcpSocket<S: SocketState = Unbound, B: SocketBuffer = Vec<u8>> {
     /// The socket file descriptor
     pub(super) fd: SocketFileDescriptor,
@@ -67,32 +87,209 @@ impl TcpSocket {
     /// # Errors
     /// - [`SocketError::ErrnoWithDescription`] if the socket could not be created
     pub fn new() -> Result<TcpSocket<Unbound>, SocketError> {
-        let fd = unsafe { sys::sceNetInetSocket(i32::from(netc::AF_INET), netc::SOCK_STREAM, 0) };
-        if fd < 0 {
-            Err(SocketError::new_errno_with_description(
-                unsafe { sys::sceNetInetGetErrno() },
-                "failed to create socket",
-            ))
-        } else {
-            let fd = SocketFileDescriptor::new(fd);
-            Ok(TcpSocket {
-                fd,
-                buffer: Vec::with_capacity(0),
-                send_flags: SocketSendFlags::empty(),
-                recv_flags: SocketRecvFlags::empty(),
-                _marker: core::marker::PhantomData,
-            })
+        Self::with_buffer(Vec::with_capacity(0))
+    }
+
+    /// Connect to the first of `addrs` accepting the connection
+    ///
+    /// The addresses are tried in order, each with a new socket.
+    ///
+    /// # Parameters
+    /// - `addrs`: The remote hosts to try
+    ///
+    /// # Errors
+    /// - The [`SocketError`] of the last address tried, if none accepted the
+    ///   connection
+    /// - [`SocketError::InvalidArgument`] if `addrs` is empty
+    pub fn connect_any(
+        addrs: impl IntoIterator<Item = SocketAddr>,
+    ) -> Result<TcpSocket<Connected>, SocketError> {
+        let mut last_error = SocketError::InvalidArgument;
+        for addr in addrs {
+            match TcpSocket::new().and_then(|socket| socket.connect(addr)) {
+                Ok(socket) => return Ok(socket),
+                Err(error) => last_error = error,
+            }
         }
+        Err(last_error)
     }
 }
 
-impl<S: SocketState> TcpSocket<S> {
+impl<S: SocketState, B: SocketBuffer> TcpSocket<S, B> {
+    /// Transition the socket to another state
+    #[must_use]
+    fn transition<T: SocketState>(self) -> TcpSocket<T, B> {
+        TcpSocket {
+            fd: self.fd,
+            buffer: self.buffer,
+            send_flags: self.send_flags,
+            recv_flags: self.recv_flags,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
     /// Return the underlying socket's file descriptor
     #[must_use]
     pub fn fd(&self) -> i32 {
         *self.fd
     }
 
+    /// Wrap a socket file descriptor created by other code (e.g. a C library)
+    ///
+    /// The socket takes ownership of the descriptor, and closes it when
+    /// dropped, unless it is released with [`into_raw_fd`](Self::into_raw_fd).
+    /// It starts with a new send buffer, and no send nor receive flags.
+    ///
+    /// # Safety
+    /// - `fd` must be an open TCP socket file descriptor, in the state `S`
+    /// - `fd` must not be closed by other code while the socket exists, nor
+    ///   wrapped more than once
+    #[must_use]
+    pub unsafe fn from_raw_fd(fd: i32) -> Self {
+        TcpSocket {
+            fd: SocketFileDescriptor::new(fd),
+            buffer: B::new(),
+            send_flags: SocketSendFlags::empty(),
+            recv_flags: SocketRecvFlags::empty(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Release the socket's file descriptor
+    ///
+    /// The descriptor is no longer closed when the socket is dropped: closing
+    /// it is up to the caller. Data still in the send buffer is discarded:
+    /// flush the socket first to send it.
+    ///
+    /// # Errors
+    /// The socket itself, if other handles share its descriptor (see
+    /// [`try_clone`](Self::try_clone)), as they would still close it.
+    pub fn into_raw_fd(self) -> Result<i32, Self> {
+        let TcpSocket {
+            fd,
+            buffer,
+            send_flags,
+            recv_flags,
+            _marker: _,
+        } = self;
+        fd.into_raw().map_err(|fd| TcpSocket {
+            fd,
+            buffer,
+            send_flags,
+            recv_flags,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    /// Create a new handle to the socket
+    ///
+    /// The handles share the socket's file descriptor, which is closed once all
+    /// of them are dropped. The new handle has the same flags, and an empty
+    /// send buffer.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the descriptor is no longer valid (e.g. it was
+    ///   closed by other code)
+    pub fn try_clone(&self) -> Result<Self, SocketError> {
+        self.fd.local_addr()?;
+        Ok(self.share())
+    }
+
+    /// Create a new handle to the socket, without checking its descriptor
+    ///
+    /// See [`TcpSocket::try_clone`].
+    pub(crate) fn share(&self) -> Self {
+        let mut buffer = self.buffer.clone();
+        buffer.clear();
+        TcpSocket {
+            fd: self.fd.clone(),
+            buffer,
+            send_flags: self.send_flags,
+            recv_flags: self.recv_flags,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// The local address of the socket
+    ///
+    /// Useful to learn the port assigned to a socket bound to port `0`.
+    /// An unbound socket reports the unspecified address and port `0`.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn local_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.local_addr()
+    }
+
+    /// Close the socket
+    ///
+    /// Unlike dropping the socket, this reports whether closing it failed.
+    /// Data still in the send buffer is discarded: flush the socket first to
+    /// send it.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be closed
+    ///
+    /// # Notes
+    /// Only the last handle to the descriptor closes it. If other handles exist
+    /// (see [`TcpSocket::try_clone`], or a [`Poller`](super::poll::Poller) the
+    /// socket is registered in), this handle is released and `Ok(())` is
+    /// returned: the socket is closed when the last handle is dropped.
+    pub fn close(self) -> Result<(), SocketError> {
+        self.fd.close()
+    }
+
+    /// Receive data into `buf`
+    fn recv(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        recv(&self.fd, buf, self.recv_flags)
+    }
+
+    /// Move the socket into or out of non-blocking mode
+    ///
+    /// In non-blocking mode, operations that cannot complete immediately fail
+    /// with [`SocketError::WouldBlock`] instead of blocking the calling thread.
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the mode could not be set
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<(), SocketError> {
+        self.fd.set_option::<NonBlocking>(nonblocking)
+    }
+
+    /// Set a socket option
+    ///
+    /// See the [`option`](super::option) module for the supported options.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the option could not be set
+    pub fn set_option<O: SetSocketOption>(&self, value: O::Value) -> Result<(), SocketError> {
+        self.fd.set_option::<O>(value)
+    }
+
+    /// Get the value of a socket option
+    ///
+    /// See the [`option`](super::option) module for the supported options.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the option could not be read
+    pub fn option<O: GetSocketOption>(&self) -> Result<O::Value, SocketError> {
+        self.fd.option::<O>()
+    }
+
+    /// Run `op` with `MSG_DONTWAIT` added to the send and receive flags
+    ///
+    /// Async operations must not block the executor, whatever the blocking
+    /// mode of the socket.
+    #[cfg(feature = "async")]
+    fn dont_wait<T>(&mut self, op: impl FnOnce(&mut Self) -> T) -> T {
+        let (send_flags, recv_flags) = (self.send_flags, self.recv_flags);
+        self.send_flags |= SocketSendFlags::MSG_DONTWAIT;
+        self.recv_flags |= SocketRecvFlags::MSG_DONTWAIT;
+        let result = op(self);
+        self.send_flags = send_flags;
+        self.recv_flags = recv_flags;
+        result
+    }
+
     /// Flags used when sending data
     #[must_use]
     pub fn send_flags(&self) -> SocketSendFlags {
@@ -116,15 +313,68 @@ impl<S: SocketState> TcpSocket<S> {
     }
 }
 
-impl TcpSocket<Unbound> {
-    #[must_use]
-    fn transition(self) -> TcpSocket<Connected> {
-        TcpSocket {
-            fd: self.fd,
-            buffer: Vec::default(),
-            send_flags: self.send_flags,
-            recv_flags: self.recv_flags,
-            _marker: core::marker::PhantomData,
+impl<B: SocketBuffer> TcpSocket<Unbound, B> {
+    /// Create a TCP socket, buffering the data to send in `buffer`
+    ///
+    /// The buffer is kept through every state transition. Sockets accepted by
+    /// a listener get an empty clone of its buffer.
+    ///
+    /// # Example
+    /// ```ignore
+    /// use psp_net::types::RingBuffer;
+    ///
+    /// let socket = TcpSocket::with_buffer(RingBuffer::with_capacity(4096))?;
+    /// let socket = socket.connect(addr)?;
+    /// ```
+    ///
+    /// # Errors
+    /// - [`SocketError::ErrnoWithDescription`] if the socket could not be created
+    pub fn with_buffer(buffer: B) -> Result<Self, SocketError> {
+        let fd = DefaultBackend::socket(i32::from(netc::AF_INET), netc::SOCK_STREAM, 0);
+        if fd < 0 {
+            Err(SocketError::new_errno_with_description(
+                DefaultBackend::errno(),
+                "failed to create socket",
+            ))
+        } else {
+            let fd = SocketFileDescriptor::new(fd);
+            Ok(TcpSocket {
+                fd,
+                buffer,
+                send_flags: SocketSendFlags::empty(),
+                recv_flags: SocketRecvFlags::empty(),
+                _marker: core::marker::PhantomData,
+            })
+        }
+    }
+
+    /// Bind the socket
+    ///
+    /// # Parameters
+    /// - `addr`: The address to bind to, if `None` binds to `0.0.0.0:0`
+    ///
+    /// # Returns
+    /// - `Ok(TcpSocket<Bound>)` if the binding was successful
+    /// - `Err(SocketError)` if the binding was unsuccessful.
+    ///
+    /// # Errors
+    /// - [`SocketError::UnsupportedAddressFamily`] if the address family is not supported (only IPv4 is supported)
+    /// - [`SocketError::Errno`] if the binding was unsuccessful
+    pub fn bind(self, addr: Option<SocketAddr>) -> Result<TcpSocket<Bound, B>, SocketError> {
+        let default_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0);
+        let addr = addr.unwrap_or(default_addr);
+        match addr {
+            SocketAddr::V4(v4) => {
+                let sockaddr = v4.to_sockaddr();
+
+                if DefaultBackend::bind(*self.fd, &sockaddr) != 0 {
+                    let errno = DefaultBackend::errno();
+                    Err(SocketError::from_errno(errno))
+                } else {
+                    Ok(self.transition())
+                }
+            }
+            SocketAddr::V6(_) => Err(SocketError::UnsupportedAddressFamily),
         }
     }
 
@@ -140,21 +390,14 @@ impl TcpSocket<Unbound> {
     /// # Errors
     /// - [`SocketError::UnsupportedAddressFamily`] if the address family is not supported (only IPv4 is supported)
     /// - Any other [`SocketError`] if the connection was unsuccessful
-    pub fn connect(self, remote: SocketAddr) -> Result<TcpSocket<Connected>, SocketError> {
+    pub fn connect(self, remote: SocketAddr) -> Result<TcpSocket<Connected, B>, SocketError> {
         match remote {
             SocketAddr::V4(v4) => {
                 let sockaddr = v4.to_sockaddr();
 
-                if unsafe {
-                    sys::sceNetInetConnect(
-                        *self.fd,
-                        &sockaddr,
-                        core::mem::size_of::<netc::sockaddr_in>() as u32,
-                    )
-                } < 0
-                {
-                    let errno = unsafe { sys::sceNetInetGetErrno() };
-                    Err(SocketError::Errno(errno))
+                if DefaultBackend::connect(*self.fd, &sockaddr) < 0 {
+                    let errno = DefaultBackend::errno();
+                    Err(SocketError::from_errno(errno))
                 } else {
                     Ok(self.transition())
                 }
@@ -162,9 +405,115 @@ impl TcpSocket<Unbound> {
             SocketAddr::V6(_) => Err(SocketError::UnsupportedAddressFamily),
         }
     }
+
+    /// Connect to a remote host, giving up after `timeout`
+    ///
+    /// The connection is started in non-blocking mode, then the socket is waited
+    /// on until it is connected or the timeout expires. The blocking mode the
+    /// socket had is restored before returning.
+    ///
+    /// # Parameters
+    /// - `remote`: The remote host to connect to
+    /// - `timeout`: The maximum time to wait for the connection to be established
+    ///
+    /// # Errors
+    /// - [`SocketError::UnsupportedAddressFamily`] if the address family is not supported (only IPv4 is supported)
+    /// - [`SocketError::TimedOut`] if the connection was not established within `timeout`
+    /// - Any other [`SocketError`] if the connection was unsuccessful
+    pub fn connect_timeout(
+        self,
+        remote: SocketAddr,
+        timeout: Duration,
+    ) -> Result<TcpSocket<Connected, B>, SocketError> {
+        let SocketAddr::V4(v4) = remote else {
+            return Err(SocketError::UnsupportedAddressFamily);
+        };
+        let sockaddr = v4.to_sockaddr();
+
+        let was_nonblocking = self.option::<NonBlocking>()?;
+        self.set_nonblocking(true)?;
+
+        let result = if DefaultBackend::connect(*self.fd, &sockaddr) < 0 {
+            let errno = DefaultBackend::errno();
+            if errno == netc::EINPROGRESS {
+                match poll::wait(&self.fd, Interest::WRITABLE, Some(timeout)) {
+                    Ok((_, Some(error))) | Err(error) => Err(error),
+                    Ok((readiness, None)) if readiness.is_empty() => Err(SocketError::TimedOut),
+                    Ok(_) => Ok(()),
+                }
+            } else {
+                Err(SocketError::from_errno(errno))
+            }
+        } else {
+            Ok(())
+        };
+
+        // restoring the mode must not hide why the connection failed
+        let restored = self.set_nonblocking(was_nonblocking);
+        result.and(restored).map(|()| self.transition())
+    }
 }
 
-impl TcpSocket<Connected> {
+impl<B: SocketBuffer> TcpSocket<Bound, B> {
+    /// Start listening for incoming connections
+    ///
+    /// # Parameters
+    /// - `backlog`: The maximum length of the queue of pending connections.
+    ///   [`DEFAULT_BACKLOG`] is a sensible default.
+    ///
+    /// # Returns
+    /// - `Ok(TcpListener)` if the socket is now listening
+    /// - `Err(SocketError)` if the socket could not listen.
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the socket could not listen
+    pub fn listen(self, backlog: i32) -> Result<TcpListener<B>, SocketError> {
+        if DefaultBackend::listen(*self.fd, backlog) != 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(self.transition())
+        }
+    }
+}
+
+impl<B: SocketBuffer> TcpSocket<Listening, B> {
+    /// Accept an incoming connection
+    ///
+    /// Blocks until a remote host connects to the socket.
+    ///
+    /// # Returns
+    /// - `Ok((TcpSocket<Connected>, SocketAddr))` if a connection was accepted.
+    ///   The returned socket is connected to the peer, whose address is also returned.
+    ///   The accepted socket inherits the send and receive flags of the listener.
+    /// - `Err(SocketError)` if the connection could not be accepted.
+    ///
+    /// # Errors
+    /// - [`SocketError::WouldBlock`] if the socket is non-blocking and no connection is pending
+    /// - [`SocketError::Errno`] if the connection could not be accepted
+    pub fn accept(&self) -> Result<(TcpSocket<Connected, B>, SocketAddr), SocketError> {
+        let mut sockaddr: netc::sockaddr = unsafe { core::mem::zeroed() };
+
+        let fd = DefaultBackend::accept(*self.fd, &mut sockaddr);
+        if fd < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            let mut buffer = self.buffer.clone();
+            buffer.clear();
+            let socket = TcpSocket {
+                fd: SocketFileDescriptor::new(fd),
+                buffer,
+                send_flags: self.send_flags,
+                recv_flags: self.recv_flags,
+                _marker: core::marker::PhantomData,
+            };
+            Ok((socket, sockaddr.to_socket_addr()))
+        }
+    }
+}
+
+impl<B: SocketBuffer> TcpSocket<Connected, B> {
     /// Read from the socket
     ///
     /// # Returns
@@ -179,76 +528,246 @@ impl TcpSocket<Connected> {
     /// the buffer. This should not be used if you want to use this socket
     /// [`EasySocket`] style.
     pub fn internal_read(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
-        let result = unsafe {
-            sys::sceNetInetRecv(
-                *self.fd,
-                buf.as_mut_ptr().cast::<c_void>(),
-                buf.len(),
-                self.recv_flags.as_i32(),
-            )
-        };
-        if result < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
-        } else {
-            Ok(result as usize)
-        }
+        self.recv(buf)
+    }
+
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+
+    /// Shut down the reading half, the writing half, or both halves of the connection
+    ///
+    /// Prefer [`TcpSocket::shutdown_write`] to shut down the writing half, so that
+    /// writing to the socket afterwards is a compile time error.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the connection could not be shut down
+    pub fn shutdown(&self, how: Shutdown) -> Result<(), SocketError> {
+        self.fd.shutdown(how)
+    }
+
+    /// Shut down the writing half of the connection
+    ///
+    /// The send buffer is flushed first, then the peer receives an end of
+    /// stream. The socket can still be read until the peer closes its side.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the send buffer could not be flushed, or the
+    ///   connection could not be shut down
+    pub fn shutdown_write(mut self) -> Result<TcpSocket<WriteShutdown, B>, SocketError> {
+        self.internal_flush()?;
+        self.fd.shutdown(Shutdown::Write)?;
+        Ok(self.transition())
     }
 
     /// Write to the socket
     ///
+    /// When no data is pending in the send buffer, `buf` is sent as is, and
+    /// only the part the network stack did not take is copied to the buffer.
+    /// Otherwise, the pending data is sent first, then `buf` is buffered.
+    ///
+    /// # Returns
+    /// The number of bytes of `buf` accepted. It is less than the length of
+    /// `buf` if the send buffer has a bounded capacity, and not enough room.
+    ///
     /// # Errors
+    /// - [`SocketError::WouldBlock`] if the socket is non-blocking, and the
+    ///   send buffer is full
     /// - A [`SocketError`] if the write was unsuccessful
     pub fn internal_write(&mut self, buf: &[u8]) -> Result<usize, SocketError> {
-        self.buffer.append_buffer(buf);
-        self.send()
+        write(&self.fd, &mut self.buffer, buf, self.send_flags)
+    }
+
+    /// Write the content of many buffers to the socket, in order
+    ///
+    /// The buffers are sent one after the other like with
+    /// [`internal_write`](Self::internal_write), without concatenating them.
+    ///
+    /// # Returns
+    /// The number of bytes accepted. Like a single write, it can be less than
+    /// the total length of `bufs`.
+    ///
+    /// # Errors
+    /// - [`SocketError::WouldBlock`] if the socket is non-blocking, and no byte
+    ///   could be accepted
+    /// - A [`SocketError`] if the write was unsuccessful
+    pub fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize, SocketError> {
+        let mut written = 0;
+        for buf in bufs {
+            match self.internal_write(buf) {
+                Ok(len) => {
+                    written += len;
+                    if len < buf.len() {
+                        break;
+                    }
+                }
+                // the data accepted so far must be reported
+                Err(_) if written > 0 => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(written)
     }
 
     fn internal_flush(&mut self) -> Result<(), SocketError> {
-        while !self.buffer.is_empty() {
-            self.send()?;
+        flush(&self.fd, &mut self.buffer, self.send_flags)
+    }
+}
+
+impl<B: SocketBuffer> TcpSocket<WriteShutdown, B> {
+    /// Read from the socket
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the read was successful. The number of bytes read, `0`
+    ///   once the peer closed its side of the connection
+    /// - `Err(SocketError)` if the read was unsuccessful.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the read was unsuccessful
+    pub fn internal_read(&self, buf: &mut [u8]) -> Result<usize, SocketError> {
+        self.recv(buf)
+    }
+
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+
+    /// Shut down the reading half of the connection as well
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the connection could not be shut down
+    pub fn shutdown_read(&self) -> Result<(), SocketError> {
+        self.fd.shutdown(Shutdown::Read)
+    }
+}
+
+/// Receive data from `fd` into `buf`
+fn recv(
+    fd: &SocketFileDescriptor,
+    buf: &mut [u8],
+    flags: SocketRecvFlags,
+) -> Result<usize, SocketError> {
+    let result = DefaultBackend::recv(**fd, buf, flags.as_i32());
+    if result < 0 {
+        let errno = DefaultBackend::errno();
+        Err(SocketError::from_errno(errno))
+    } else {
+        Ok(result as usize)
+    }
+}
+
+/// Write `buf` to `fd`, through the send buffer `buffer`
+///
+/// See [`TcpSocket::internal_write`].
+fn write<B: SocketBuffer>(
+    fd: &SocketFileDescriptor,
+    buffer: &mut B,
+    buf: &[u8],
+    flags: SocketSendFlags,
+) -> Result<usize, SocketError> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    if !buffer.is_empty() {
+        // pending data goes out first, to keep the data in order
+        match send_pending(fd, buffer, flags) {
+            Ok(_) | Err(SocketError::WouldBlock) => {}
+            Err(error) => return Err(error),
         }
-        Ok(())
     }
 
-    fn send(&mut self) -> Result<usize, SocketError> {
-        let result = unsafe {
-            sys::sceNetInetSend(
-                *self.fd,
-                self.buffer.as_slice().as_ptr().cast::<c_void>(),
-                self.buffer.len(),
-                self.send_flags.as_i32(),
-            )
-        };
-        if result < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
-        } else {
-            self.buffer.shift_left_buffer(result as usize);
-            Ok(result as usize)
+    // nothing pending: send straight from `buf`, and only buffer the rest
+    let sent = if buffer.is_empty() {
+        match send(fd, buf, flags) {
+            Ok(sent) => sent,
+            Err(SocketError::WouldBlock) => 0,
+            Err(error) => return Err(error),
         }
+    } else {
+        0
+    };
+
+    let len = (buf.len() - sent).min(buffer.spare_capacity());
+    buffer.append_buffer(&buf[sent..sent + len]);
+    if sent + len == 0 {
+        return Err(SocketError::WouldBlock);
     }
+    Ok(sent + len)
 }
 
-impl<S: SocketState> ErrorType for TcpSocket<S> {
+/// Send all the data of the send buffer `buffer` to `fd`
+fn flush<B: SocketBuffer>(
+    fd: &SocketFileDescriptor,
+    buffer: &mut B,
+    flags: SocketSendFlags,
+) -> Result<(), SocketError> {
+    while !buffer.is_empty() {
+        send_pending(fd, buffer, flags)?;
+    }
+    Ok(())
+}
+
+/// Send the data at the front of the send buffer `buffer` to `fd`
+fn send_pending<B: SocketBuffer>(
+    fd: &SocketFileDescriptor,
+    buffer: &mut B,
+    flags: SocketSendFlags,
+) -> Result<usize, SocketError> {
+    let sent = send(fd, buffer.as_slice(), flags)?;
+    buffer.shift_left_buffer(sent);
+    Ok(sent)
+}
+
+/// Send `buf` to `fd`, bypassing any send buffer
+fn send(
+    fd: &SocketFileDescriptor,
+    buf: &[u8],
+    flags: SocketSendFlags,
+) -> Result<usize, SocketError> {
+    let result = DefaultBackend::send(**fd, buf, flags.as_i32());
+    if result < 0 {
+        let errno = DefaultBackend::errno();
+        Err(SocketError::from_errno(errno))
+    } else {
+        Ok(result as usize)
+    }
+}
+
+impl<S: SocketState, B: SocketBuffer> ErrorType for TcpSocket<S, B> {
     type Error = SocketError;
 }
 
-impl<S: SocketState> OptionType for TcpSocket<S> {
+impl<S: SocketState, B: SocketBuffer> OptionType for TcpSocket<S, B> {
     type Options<'a> = SocketOptions;
 }
 
-impl Open<'_, '_> for TcpSocket<Unbound> {
-    type Return = TcpSocket<Connected>;
+impl<B: SocketBuffer> Open<'_, '_> for TcpSocket<Unbound, B> {
+    type Return = TcpSocket<Connected, B>;
     /// Return a TCP socket connected to the remote specified in `options`
+    ///
+    /// If `options` has a [connect timeout](SocketOptions::connect_timeout),
+    /// the connection is established with [`TcpSocket::connect_timeout`].
     fn open(self, options: &'_ Self::Options<'_>) -> Result<Self::Return, Self::Error>
     where
         Self: Sized,
     {
-        let socket = self.connect(options.remote())?;
+        let socket = match options.connect_timeout() {
+            Some(timeout) => self.connect_timeout(options.remote(), timeout)?,
+            None => self.connect(options.remote())?,
+        };
         Ok(socket)
     }
 }
 
-impl Read for TcpSocket<Connected> {
+impl<B: SocketBuffer> Read for TcpSocket<Connected, B> {
     /// Read from the socket
     ///
     /// # Parameters
@@ -266,7 +785,7 @@ impl Read for TcpSocket<Connected> {
     }
 }
 
-impl Write for TcpSocket<Connected> {
+impl<B: SocketBuffer> Write for TcpSocket<Connected, B> {
     /// Write to the socket
     ///
     /// # Errors
@@ -285,4 +804,343 @@ impl Write for TcpSocket<Connected> {
     }
 }
 
-impl EasySocket for TcpSocket<Connected> {}
+#[cfg(feature = "async")]
+impl<B: SocketBuffer> embedded_io_async::Read for TcpSocket<Connected, B> {
+    /// Read from the socket, without blocking the executor
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the read was unsuccessful
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        poll::retry(self.fd.clone(), Interest::READABLE, || {
+            self.dont_wait(|socket| socket.internal_read(buf))
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<B: SocketBuffer> embedded_io_async::Write for TcpSocket<Connected, B> {
+    /// Write to the socket, without blocking the executor
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the write was unsuccessful
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        poll::retry(self.fd.clone(), Interest::WRITABLE, || {
+            self.dont_wait(|socket| socket.internal_write(buf))
+        })
+        .await
+    }
+
+    /// Flush the socket, without blocking the executor
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the flush was unsuccessful
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        poll::retry(self.fd.clone(), Interest::WRITABLE, || {
+            self.dont_wait(Self::internal_flush)
+        })
+        .await
+    }
+}
+
+impl<B: SocketBuffer> ReadReady for TcpSocket<Connected, B> {
+    /// Whether the socket can be read without blocking
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be probed
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) = poll::probe(&self.fd, Interest::READABLE)?;
+        Ok(readiness.contains(Interest::READABLE))
+    }
+}
+
+impl<B: SocketBuffer> WriteReady for TcpSocket<Connected, B> {
+    /// Whether the socket can be written without blocking
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be probed
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) = poll::probe(&self.fd, Interest::WRITABLE)?;
+        Ok(readiness.contains(Interest::WRITABLE))
+    }
+}
+
+impl<B: SocketBuffer> Pollable for TcpSocket<Connected, B> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
+
+impl<B: SocketBuffer> EasySocket for TcpSocket<Connected, B> {}
+
+impl<B: SocketBuffer> Read for TcpSocket<WriteShutdown, B> {
+    /// Read from the socket
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the read was unsuccessful
+    fn read<'m>(&'m mut self, buf: &'m mut [u8]) -> Result<usize, Self::Error> {
+        self.internal_read(buf)
+    }
+}
+
+impl<B: SocketBuffer> ReadReady for TcpSocket<WriteShutdown, B> {
+    /// Whether the socket can be read without blocking
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be probed
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) = poll::probe(&self.fd, Interest::READABLE)?;
+        Ok(readiness.contains(Interest::READABLE))
+    }
+}
+
+impl<B: SocketBuffer> Pollable for TcpSocket<WriteShutdown, B> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+    use crate::types::RingBuffer;
+    use std::io::{Read as _, Write as _};
+
+    /// Spawn a TCP server echoing back what it receives to a single client
+    fn echo_server() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 64];
+            loop {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => stream.write_all(&buf[..n]).unwrap(),
+                }
+            }
+        });
+        addr
+    }
+
+    #[test]
+    fn connect_and_echo() {
+        let addr = echo_server();
+        let mut socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+
+        socket.write_all(b"hello").unwrap();
+        let mut buf = [0u8; 5];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hello");
+    }
+
+    #[test]
+    fn ring_buffer_is_kept_through_transitions() {
+        let addr = echo_server();
+        let buffer = RingBuffer::with_capacity(4);
+        let mut socket: TcpSocket<Connected, RingBuffer> = TcpSocket::with_buffer(buffer)
+            .unwrap()
+            .connect(addr)
+            .unwrap();
+        assert_eq!(socket.buffer.capacity(), 4);
+
+        let message = b"longer than the buffer";
+        socket.write_all(message).unwrap();
+        socket.flush().unwrap();
+        let mut buf = [0u8; 22];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, message);
+    }
+
+    #[test]
+    fn write_vectored_sends_without_buffering() {
+        let addr = echo_server();
+        let mut socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+
+        let written = socket
+            .write_vectored(&[b"GET / HTTP/1.1\r\n\r\n", b"", b"body"])
+            .unwrap();
+        assert_eq!(written, 22);
+        assert!(socket.buffer.is_empty());
+        assert_eq!(socket.buffer.capacity(), 0);
+
+        let mut buf = [0u8; 22];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"GET / HTTP/1.1\r\n\r\nbody");
+    }
+
+    #[test]
+    fn split_halves_run_on_separate_threads() {
+        let addr = echo_server();
+        let socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+
+        let (mut read, mut write) = socket.into_split();
+        let writer = std::thread::spawn(move || {
+            write.write_all(b"ping").unwrap();
+            write.flush().unwrap();
+            write
+        });
+        let mut buf = [0u8; 4];
+        read.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+        let mut socket = read.reunite(writer.join().unwrap()).unwrap();
+
+        let (mut read, mut write) = socket.split();
+        std::thread::scope(|s| {
+            s.spawn(|| write.write_all(b"pong").unwrap());
+            read.read_exact(&mut buf).unwrap();
+        });
+        assert_eq!(&buf, b"pong");
+
+        let (read, _) = socket.into_split();
+        let other = TcpSocket::new().unwrap().connect(echo_server()).unwrap();
+        let (_, write) = other.into_split();
+        assert!(read.reunite(write).is_err());
+    }
+
+    #[test]
+    fn raw_fd_round_trip() {
+        let addr = echo_server();
+        let socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+
+        let mut clone = socket.try_clone().unwrap();
+        let socket = socket.into_raw_fd().unwrap_err();
+        clone.write_all(b"clone").unwrap();
+        drop(clone);
+
+        let fd = socket.into_raw_fd().unwrap();
+        let mut socket: TcpSocket<Connected> = unsafe { TcpSocket::from_raw_fd(fd) };
+        assert_eq!(socket.peer_addr().unwrap(), addr);
+        let mut buf = [0u8; 5];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"clone");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_echo_does_not_block() {
+        use embedded_io_async::{Read as AsyncRead, Write as AsyncWrite};
+
+        let addr = echo_server();
+        let mut socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+
+        crate::executor::block_on(async {
+            AsyncWrite::write_all(&mut socket, b"async").await.unwrap();
+            AsyncWrite::flush(&mut socket).await.unwrap();
+            let mut buf = [0u8; 5];
+            AsyncRead::read_exact(&mut socket, &mut buf).await.unwrap();
+            assert_eq!(&buf, b"async");
+        });
+        // the blocking mode of the socket is left untouched
+        assert_eq!(socket.recv_flags(), SocketRecvFlags::empty());
+        assert!(!socket.option::<NonBlocking>().unwrap());
+    }
+
+    #[test]
+    fn open_with_connect_timeout() {
+        let addr = echo_server();
+        let mut options = SocketOptions::new(addr);
+        options.set_connect_timeout(Some(Duration::from_secs(1)));
+        let mut socket = TcpSocket::new().unwrap().open(&options).unwrap();
+
+        socket.write_all(b"ping").unwrap();
+        let mut buf = [0u8; 4];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+        assert!(!socket.option::<NonBlocking>().unwrap());
+    }
+
+    #[test]
+    fn connect_timeout_expires() {
+        // a listener whose backlog is full drops the connection requests
+        let listener = TcpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap()
+            .listen(0)
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let timeout = Duration::from_millis(50);
+
+        let mut pending = Vec::new();
+        let error = loop {
+            let start = std::time::Instant::now();
+            match TcpSocket::new().unwrap().connect_timeout(addr, timeout) {
+                Ok(socket) if pending.len() < 8 => pending.push(socket),
+                Ok(_) => panic!("the backlog of the listener never filled up"),
+                Err(error) => {
+                    assert!(start.elapsed() >= timeout);
+                    break error;
+                }
+            }
+        };
+        assert_eq!(error, SocketError::TimedOut);
+    }
+
+    #[test]
+    fn connect_refused() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let result = TcpSocket::new().unwrap().connect(addr);
+        assert_eq!(result.unwrap_err(), SocketError::ConnectionRefused);
+    }
+
+    #[test]
+    fn nonblocking_read_would_block() {
+        let addr = echo_server();
+        let socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+        socket.set_nonblocking(true).unwrap();
+
+        let mut buf = [0u8; 4];
+        assert!(socket.internal_read(&mut buf).unwrap_err().is_would_block());
+    }
+
+    #[test]
+    fn shutdown_write_sends_end_of_stream() {
+        let addr = echo_server();
+        let mut socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+        socket.write_all(b"bye").unwrap();
+
+        // the server echoes, then closes once it reads the end of stream
+        let mut socket = socket.shutdown_write().unwrap();
+        let mut buf = [0u8; 3];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"bye");
+        assert_eq!(socket.read(&mut buf).unwrap(), 0);
+        socket.close().unwrap();
+    }
+
+    #[test]
+    fn close_unregistered_socket() {
+        TcpSocket::new().unwrap().close().unwrap();
+    }
+
+    #[test]
+    fn listen_and_accept() {
+        let listener = TcpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap()
+            .listen(DEFAULT_BACKLOG)
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        assert_ne!(addr.port(), 0);
+
+        let client = std::thread::spawn(move || {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream.write_all(b"hi").unwrap();
+            stream.local_addr().unwrap()
+        });
+
+        let (mut socket, peer) = listener.accept().unwrap();
+        let mut buf = [0u8; 2];
+        socket.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"hi");
+        let client = client.join().unwrap();
+        assert_eq!(peer, client);
+        assert_eq!(socket.peer_addr().unwrap(), client);
+        assert_eq!(socket.local_addr().unwrap(), addr);
+    }
+}
@@ -0,0 +1,287 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt::{self, Debug};
+use embedded_io::{ErrorType, Read, Write};
+
+use crate::{
+    traits::SocketBuffer,
+    types::{SocketRecvFlags, SocketSendFlags},
+};
+
+#[cfg(feature = "async")]
+use super::super::poll::{self, Interest};
+use super::super::{
+    error::SocketError, poll::Pollable, sce::SocketFileDescriptor, state::Connected, SocketAddr,
+};
+use super::{flush, recv, write, TcpSocket};
+
+/// The reading half of a [`TcpSocket`], borrowed from it
+///
+/// Created by [`TcpSocket::split`].
+#[derive(Debug)]
+pub struct ReadHalf<'a> {
+    /// The socket file descriptor
+    fd: &'a SocketFileDescriptor,
+    /// flags for recv calls
+    recv_flags: SocketRecvFlags,
+}
+
+/// The writing half of a [`TcpSocket`], borrowed from it
+///
+/// Created by [`TcpSocket::split`]. Writes go through the send buffer of the
+/// socket.
+#[derive(Debug)]
+pub struct WriteHalf<'a, B: SocketBuffer = Vec<u8>> {
+    /// The socket file descriptor
+    fd: &'a SocketFileDescriptor,
+    /// The send buffer of the socket
+    buffer: &'a mut B,
+    /// flags for send calls
+    send_flags: SocketSendFlags,
+}
+
+/// The reading half of a [`TcpSocket`]
+///
+/// Created by [`TcpSocket::into_split`]. It can be moved to another thread
+/// than the writing half.
+#[derive(Debug)]
+pub struct OwnedReadHalf {
+    /// The socket file descriptor
+    fd: SocketFileDescriptor,
+    /// flags for recv calls
+    recv_flags: SocketRecvFlags,
+}
+
+/// The writing half of a [`TcpSocket`]
+///
+/// Created by [`TcpSocket::into_split`]. It can be moved to another thread
+/// than the reading half.
+///
+/// # Notes
+/// Like for the socket, data still in the send buffer when the half is
+/// dropped is discarded: flush it first.
+#[derive(Debug)]
+pub struct OwnedWriteHalf<B: SocketBuffer = Vec<u8>> {
+    /// The socket file descriptor
+    fd: SocketFileDescriptor,
+    /// The send buffer of the socket
+    buffer: B,
+    /// flags for send calls
+    send_flags: SocketSendFlags,
+}
+
+/// Error returned by [`OwnedReadHalf::reunite`] when the halves do not come
+/// from the same socket
+///
+/// It gives the halves back.
+pub struct ReuniteError<B: SocketBuffer = Vec<u8>>(pub OwnedReadHalf, pub OwnedWriteHalf<B>);
+
+impl<B: SocketBuffer> Debug for ReuniteError<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReuniteError").finish_non_exhaustive()
+    }
+}
+
+impl<B: SocketBuffer> TcpSocket<Connected, B> {
+    /// Split the socket into a reading half and a writing half, borrowed from it
+    ///
+    /// The halves can be used at the same time, from different threads (e.g.
+    /// with `std::thread::scope`).
+    ///
+    /// # Returns
+    /// The reading half, and the writing half, which uses the send buffer and
+    /// the send flags of the socket.
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_, B>) {
+        (
+            ReadHalf {
+                fd: &self.fd,
+                recv_flags: self.recv_flags,
+            },
+            WriteHalf {
+                fd: &self.fd,
+                buffer: &mut self.buffer,
+                send_flags: self.send_flags,
+            },
+        )
+    }
+
+    /// Split the socket into a reading half and a writing half
+    ///
+    /// The halves share the socket's file descriptor, which is closed once both
+    /// are dropped. Use [`OwnedReadHalf::reunite`] to get the socket back.
+    ///
+    /// # Returns
+    /// The reading half, and the writing half, which takes the send buffer and
+    /// the send flags of the socket.
+    #[must_use]
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf<B>) {
+        (
+            OwnedReadHalf {
+                fd: self.fd.clone(),
+                recv_flags: self.recv_flags,
+            },
+            OwnedWriteHalf {
+                fd: self.fd,
+                buffer: self.buffer,
+                send_flags: self.send_flags,
+            },
+        )
+    }
+}
+
+impl ReadHalf<'_> {
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+}
+
+impl<B: SocketBuffer> WriteHalf<'_, B> {
+    /// The socket file descriptor, the send buffer, and the send flags
+    fn parts(&mut self) -> (&SocketFileDescriptor, &mut B, SocketSendFlags) {
+        (self.fd, self.buffer, self.send_flags)
+    }
+
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+}
+
+impl OwnedReadHalf {
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+
+    /// Put the halves back together
+    ///
+    /// # Errors
+    /// - [`ReuniteError`] if the halves do not come from the same socket
+    pub fn reunite<B: SocketBuffer>(
+        self,
+        write: OwnedWriteHalf<B>,
+    ) -> Result<TcpSocket<Connected, B>, ReuniteError<B>> {
+        if !Arc::ptr_eq(&self.fd.0, &write.fd.0) {
+            return Err(ReuniteError(self, write));
+        }
+        Ok(TcpSocket {
+            fd: write.fd,
+            buffer: write.buffer,
+            send_flags: write.send_flags,
+            recv_flags: self.recv_flags,
+            _marker: core::marker::PhantomData,
+        })
+    }
+}
+
+impl<B: SocketBuffer> OwnedWriteHalf<B> {
+    /// The socket file descriptor, the send buffer, and the send flags
+    fn parts(&mut self) -> (&SocketFileDescriptor, &mut B, SocketSendFlags) {
+        (&self.fd, &mut self.buffer, self.send_flags)
+    }
+
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+}
+
+/// Implement the reading traits for a reading half
+macro_rules! read_half {
+    ($half:ty) => {
+        impl ErrorType for $half {
+            type Error = SocketError;
+        }
+
+        impl Read for $half {
+            /// Read from the socket
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+                recv(&self.fd, buf, self.recv_flags)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl embedded_io_async::Read for $half {
+            /// Read from the socket, without blocking the executor
+            async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+                let flags = self.recv_flags | SocketRecvFlags::MSG_DONTWAIT;
+                let fd: &SocketFileDescriptor = &self.fd;
+                poll::retry(fd.clone(), Interest::READABLE, || recv(fd, buf, flags)).await
+            }
+        }
+
+        impl Pollable for $half {
+            fn socket_fd(&self) -> &SocketFileDescriptor {
+                &self.fd
+            }
+        }
+    };
+}
+
+/// Implement the writing traits for a writing half
+macro_rules! write_half {
+    ($half:ty) => {
+        impl<B: SocketBuffer> ErrorType for $half {
+            type Error = SocketError;
+        }
+
+        impl<B: SocketBuffer> Write for $half {
+            /// Write to the socket
+            ///
+            /// See [`TcpSocket::internal_write`].
+            fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                let (fd, buffer, flags) = self.parts();
+                write(fd, buffer, buf, flags)
+            }
+
+            /// Send the data of the send buffer
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                let (fd, buffer, flags) = self.parts();
+                flush(fd, buffer, flags)
+            }
+        }
+
+        #[cfg(feature = "async")]
+        impl<B: SocketBuffer> embedded_io_async::Write for $half {
+            /// Write to the socket, without blocking the executor
+            async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                let (fd, buffer, flags) = self.parts();
+                let flags = flags | SocketSendFlags::MSG_DONTWAIT;
+                poll::retry(fd.clone(), Interest::WRITABLE, || {
+                    write(fd, buffer, buf, flags)
+                })
+                .await
+            }
+
+            /// Send the data of the send buffer, without blocking the executor
+            async fn flush(&mut self) -> Result<(), Self::Error> {
+                let (fd, buffer, flags) = self.parts();
+                let flags = flags | SocketSendFlags::MSG_DONTWAIT;
+                poll::retry(fd.clone(), Interest::WRITABLE, || flush(fd, buffer, flags)).await
+            }
+        }
+
+        impl<B: SocketBuffer> Pollable for $half {
+            fn socket_fd(&self) -> &SocketFileDescriptor {
+                &self.fd
+            }
+        }
+    };
+}
+
+read_half!(ReadHalf<'_>);
+read_half!(OwnedReadHalf);
+write_half!(WriteHalf<'_, B>);
+write_half!(OwnedWriteHalf<B>);
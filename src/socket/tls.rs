@@ -16,10 +16,22 @@ use crate::{
 };
 
 use super::{
+    error::SocketError,
+    poll::{self, Interest, Pollable},
+    sce::SocketFileDescriptor,
     state::{Connected, NotReady, Ready, SocketState},
     tcp::TcpSocket,
+    SocketAddr,
 };
 
+#[cfg(feature = "async")]
+mod asynch;
+mod split;
+#[cfg(feature = "async")]
+pub use asynch::AsyncTlsSocket;
+pub use embedded_tls::blocking::SplitConnectionState;
+pub use split::{TlsReadHalf, TlsReuniteError, TlsWriteHalf};
+
 lazy_static::lazy_static! {
     static ref REGEX: Regex = Regex::new("\r|\0").unwrap();
 }
@@ -34,14 +46,16 @@ pub const MAX_FRAGMENT_LENGTH: u16 = 16_384;
 /// For the Debug trait a dummy implementation is provided.
 pub struct TlsSocket<'a, S: SocketState = NotReady> {
     /// The TLS connection
-    tls_connection: TlsConnection<'a, TcpSocket<Connected>, Aes128GcmSha256>,
+    tls_connection: TlsConnection<'a, Transport, Aes128GcmSha256>,
     /// The TLS config
     tls_config: TlsConfig<'a, Aes128GcmSha256>,
+    /// The file descriptor of the underlying socket
+    fd: SocketFileDescriptor,
     /// marker for the socket state
     _marker: core::marker::PhantomData<S>,
 }
 
-impl Debug for TlsSocket<'_> {
+impl<S: SocketState> Debug for TlsSocket<'_, S> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("TlsSocket").finish()
     }
@@ -62,10 +76,15 @@ impl<'a> TlsSocket<'_> {
     ///
     /// # Example
     /// ```no_run
+    /// # use psp_net::socket::{error::TlsSocketError, state::Connected, tcp::TcpSocket, tls::TlsSocket};
+    /// # use psp_net::{traits::io::Open, types::TlsSocketOptions};
+    /// # fn example(tcp_socket: TcpSocket<Connected>, options: TlsSocketOptions<'_>) -> Result<(), TlsSocketError> {
     /// let mut read_buf = TlsSocket::new_buffer();
     /// let mut write_buf = TlsSocket::new_buffer();
     /// let tls_socket = TlsSocket::new(tcp_socket, &mut read_buf, &mut write_buf);
     /// let tls_socket = tls_socket.open(&options)?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// # Notes
@@ -76,12 +95,14 @@ impl<'a> TlsSocket<'_> {
         record_write_buf: &'a mut [u8],
     ) -> TlsSocket<'a, NotReady> {
         let tls_config: TlsConfig<'_, Aes128GcmSha256> = TlsConfig::new();
+        let fd = socket.fd.clone();
 
-        let tls_connection: TlsConnection<TcpSocket<Connected>, Aes128GcmSha256> =
-            TlsConnection::new(socket, record_read_buf, record_write_buf);
+        let tls_connection: TlsConnection<Transport, Aes128GcmSha256> =
+            TlsConnection::new(Transport(socket), record_read_buf, record_write_buf);
         TlsSocket {
             tls_connection,
             tls_config,
+            fd,
             _marker: core::marker::PhantomData,
         }
     }
@@ -94,9 +115,12 @@ impl<'a> TlsSocket<'_> {
     ///
     /// # Example
     /// ```no_run
+    /// # use psp_net::socket::{state::Connected, tcp::TcpSocket, tls::TlsSocket};
+    /// # fn example(tcp_socket: TcpSocket<Connected>) {
     /// let mut read_buf = TlsSocket::new_buffer();
     /// let mut write_buf = TlsSocket::new_buffer();
     /// let tls_socket = TlsSocket::new(tcp_socket, &mut read_buf, &mut write_buf);
+    /// # }
     /// ```
     #[must_use]
     pub fn new_buffer() -> [u8; MAX_FRAGMENT_LENGTH as usize] {
@@ -104,6 +128,50 @@ impl<'a> TlsSocket<'_> {
     }
 }
 
+impl<S: SocketState> TlsSocket<'_, S> {
+    /// The local address of the underlying socket
+    ///
+    /// # Errors
+    /// - [`embedded_tls::TlsError`] if the address could not be retrieved
+    pub fn local_addr(&self) -> Result<SocketAddr, embedded_tls::TlsError> {
+        self.fd.local_addr().map_err(|e| to_tls_error(&e))
+    }
+
+    /// The address of the peer the underlying socket is connected to
+    ///
+    /// # Errors
+    /// - [`embedded_tls::TlsError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, embedded_tls::TlsError> {
+        self.fd.peer_addr().map_err(|e| to_tls_error(&e))
+    }
+
+    /// Close the TLS connection and the underlying socket.
+    ///
+    /// A `close_notify` alert is sent to the peer before closing the socket.
+    /// Unlike dropping the socket, this reports whether closing it failed.
+    ///
+    /// # Errors
+    /// - [`embedded_tls::TlsError`] if the alert could not be sent, or the
+    ///   socket could not be closed. The socket is closed in either case.
+    ///
+    /// # Notes
+    /// Only the last handle to the underlying descriptor closes it. If the
+    /// socket is registered in a [`Poller`](super::poll::Poller), the
+    /// descriptor stays open, and `Ok(())` is returned once the alert is sent:
+    /// it is closed when the poller releases its handle.
+    pub fn close(self) -> Result<(), embedded_tls::TlsError> {
+        // release our handle first, so that closing the socket closes the descriptor
+        drop(self.fd);
+        match self.tls_connection.close() {
+            Ok(Transport(socket)) => socket.close().map_err(|e| to_tls_error(&e)),
+            Err((socket, error)) => {
+                drop(socket);
+                Err(error)
+            }
+        }
+    }
+}
+
 impl TlsSocket<'_, Ready> {
     /// Write all data to the TLS connection.
     ///
@@ -165,8 +233,15 @@ where
     ///
     /// # Example
     /// ```no_run
+    /// # use psp_net::socket::{error::TlsSocketError, state::Connected, tcp::TcpSocket, tls::TlsSocket};
+    /// # use psp_net::{traits::io::Open, types::TlsSocketOptions};
+    /// # fn example(tcp_socket: TcpSocket<Connected>, options: TlsSocketOptions<'_>) -> Result<(), TlsSocketError> {
+    /// # let mut read_buf = TlsSocket::new_buffer();
+    /// # let mut write_buf = TlsSocket::new_buffer();
     /// let tls_socket = TlsSocket::new(tcp_socket, &mut read_buf, &mut write_buf);
     /// let tls_socket = tls_socket.open(&options)?;
+    /// # Ok(())
+    /// # }
     /// ```
     ///
     /// # Notes
@@ -180,30 +255,11 @@ where
 
         let mut tls_socket: TlsSocket<Ready> = TlsSocket {
             tls_connection: self.tls_connection,
-            tls_config: self.tls_config,
+            tls_config: configure(self.tls_config, options),
+            fd: self.fd,
             _marker: core::marker::PhantomData,
         };
 
-        tls_socket.tls_config = tls_socket
-            .tls_config
-            .with_server_name(options.server_name());
-
-        if options.rsa_signatures_enabled() {
-            tls_socket.tls_config = tls_socket.tls_config.enable_rsa_signatures();
-        }
-
-        if options.reset_max_fragment_length() {
-            tls_socket.tls_config = tls_socket.tls_config.reset_max_fragment_length();
-        }
-
-        if let Some(cert) = options.cert() {
-            tls_socket.tls_config = tls_socket.tls_config.with_cert(cert.clone());
-        }
-
-        if let Some(ca) = options.ca() {
-            tls_socket.tls_config = tls_socket.tls_config.with_ca(ca.clone());
-        }
-
         let tls_context = TlsContext::new(&tls_socket.tls_config, &mut rng);
         tls_socket
             .tls_connection
@@ -246,4 +302,189 @@ impl embedded_io::Write for TlsSocket<'_, Ready> {
     }
 }
 
+impl embedded_io::ReadReady for TlsSocket<'_, Ready> {
+    /// Whether the underlying socket can be read without blocking
+    ///
+    /// # Notes
+    /// Readiness is that of the underlying TCP socket. Since a TLS record may be
+    /// received in more than one segment, a read may still block until the
+    /// whole record is received.
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) =
+            poll::probe(&self.fd, Interest::READABLE).map_err(|e| to_tls_error(&e))?;
+        Ok(readiness.contains(Interest::READABLE))
+    }
+}
+
+impl embedded_io::WriteReady for TlsSocket<'_, Ready> {
+    /// Whether the underlying socket can be written without blocking
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) =
+            poll::probe(&self.fd, Interest::WRITABLE).map_err(|e| to_tls_error(&e))?;
+        Ok(readiness.contains(Interest::WRITABLE))
+    }
+}
+
+impl Pollable for TlsSocket<'_, Ready> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
+
+/// Apply the TLS `options` to `config`
+fn configure<'a>(
+    mut config: TlsConfig<'a, Aes128GcmSha256>,
+    options: &'a TlsSocketOptions<'_>,
+) -> TlsConfig<'a, Aes128GcmSha256> {
+    config = config.with_server_name(options.server_name());
+
+    if options.rsa_signatures_enabled() {
+        config = config.enable_rsa_signatures();
+    }
+
+    if options.reset_max_fragment_length() {
+        config = config.reset_max_fragment_length();
+    }
+
+    if let Some(cert) = options.cert() {
+        config = config.with_cert(cert.clone());
+    }
+
+    if let Some(ca) = options.ca() {
+        config = config.with_ca(ca.clone());
+    }
+
+    config
+}
+
+/// The socket a TLS connection runs over
+///
+/// `embedded-tls` clones the socket to split the connection, while
+/// [`TcpSocket`] is not [`Clone`]: a clone is another handle to the socket,
+/// like one made with [`TcpSocket::try_clone`].
+struct Transport(TcpSocket<Connected>);
+
+impl Clone for Transport {
+    fn clone(&self) -> Self {
+        Transport(self.0.share())
+    }
+}
+
+impl ErrorType for Transport {
+    type Error = SocketError;
+}
+
+impl Read for Transport {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for Transport {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.0.flush()
+    }
+}
+
+/// Convert a [`SocketError`] of the underlying socket into a [`embedded_tls::TlsError`]
+fn to_tls_error(error: &SocketError) -> embedded_tls::TlsError {
+    embedded_tls::TlsError::Io(embedded_io::Error::kind(error))
+}
+
 impl EasySocket for TlsSocket<'_, Ready> {}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    #[test]
+    fn open_fails_against_plain_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+        });
+
+        let socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+        let mut read_buf = TlsSocket::new_buffer();
+        let mut write_buf = TlsSocket::new_buffer();
+        let options = TlsSocketOptions::new(0, "localhost");
+        let tls_socket = TlsSocket::new(socket, &mut read_buf, &mut write_buf);
+        assert_eq!(tls_socket.peer_addr().unwrap(), addr);
+        assert!(tls_socket.open(&options).is_err());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_open_fails_against_plain_server() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+        });
+
+        let socket = TcpSocket::new().unwrap().connect(addr).unwrap();
+        let mut read_buf = TlsSocket::new_buffer();
+        let mut write_buf = TlsSocket::new_buffer();
+        let options = TlsSocketOptions::new(0, "localhost");
+        let tls_socket = AsyncTlsSocket::new(socket, &mut read_buf, &mut write_buf);
+        assert_eq!(tls_socket.peer_addr().unwrap(), addr);
+        assert!(crate::executor::block_on(tls_socket.open(&options)).is_err());
+    }
+
+    /// A TLS socket connected to a new local peer, marked ready without a
+    /// handshake: it can be split, but not used
+    fn unopened_ready_socket<'a>(
+        read_buf: &'a mut [u8],
+        write_buf: &'a mut [u8],
+    ) -> (TlsSocket<'a, Ready>, std::net::TcpStream) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let socket = TcpSocket::new()
+            .unwrap()
+            .connect(listener.local_addr().unwrap())
+            .unwrap();
+        let (peer, _) = listener.accept().unwrap();
+
+        let socket = TlsSocket::new(socket, read_buf, write_buf);
+        let socket = TlsSocket {
+            tls_connection: socket.tls_connection,
+            tls_config: socket.tls_config,
+            fd: socket.fd,
+            _marker: core::marker::PhantomData,
+        };
+        (socket, peer)
+    }
+
+    #[test]
+    fn unsplit_checks_the_halves_belong_together() {
+        let (mut read_a, mut write_a) = (TlsSocket::new_buffer(), TlsSocket::new_buffer());
+        let (mut read_b, mut write_b) = (TlsSocket::new_buffer(), TlsSocket::new_buffer());
+        let (first, first_peer) = unopened_ready_socket(&mut read_a, &mut write_a);
+        let (second, _second_peer) = unopened_ready_socket(&mut read_b, &mut write_b);
+
+        let mut first_state = SplitConnectionState::default();
+        let mut second_state = SplitConnectionState::default();
+        let (first_read, first_write) = first.split(&mut first_state);
+        let (second_read, second_write) = second.split(&mut second_state);
+        assert_eq!(
+            first_read.peer_addr().unwrap(),
+            first_peer.local_addr().unwrap()
+        );
+
+        let TlsReuniteError(first_read, second_write) =
+            TlsSocket::unsplit(first_read, second_write).unwrap_err();
+        let TlsReuniteError(second_read, first_write) =
+            TlsSocket::unsplit(second_read, first_write).unwrap_err();
+
+        let first = TlsSocket::unsplit(first_read, first_write).unwrap();
+        assert_eq!(first.peer_addr().unwrap(), first_peer.local_addr().unwrap());
+        assert!(TlsSocket::unsplit(second_read, second_write).is_ok());
+    }
+}
@@ -0,0 +1,187 @@
+use core::fmt::Debug;
+
+use embedded_io::ErrorType;
+use embedded_tls::{Aes128GcmSha256, NoVerify, TlsConfig, TlsConnection, TlsContext, TlsError};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::types::TlsSocketOptions;
+
+use super::super::{
+    poll::Pollable,
+    sce::SocketFileDescriptor,
+    state::{Connected, NotReady, Ready, SocketState},
+    tcp::TcpSocket,
+    SocketAddr,
+};
+use super::{configure, to_tls_error};
+
+/// An async TLS socket.
+/// This is a wrapper around a [`TcpSocket`] that provides a TLS connection,
+/// implementing [`embedded_io_async::Read`] and [`embedded_io_async::Write`].
+///
+/// Unlike [`TlsSocket`](super::TlsSocket), whose TLS connection is blocking, it
+/// uses the async TLS connection of `embedded-tls`, driven by the async
+/// implementations of [`TcpSocket`].
+///
+/// # Example
+/// ```ignore
+/// let mut read_buf = TlsSocket::new_buffer();
+/// let mut write_buf = TlsSocket::new_buffer();
+/// let tls_socket = AsyncTlsSocket::new(tcp_socket, &mut read_buf, &mut write_buf);
+/// let mut tls_socket = tls_socket.open(&options).await?;
+/// tls_socket.write_all(b"GET / HTTP/1.1\r\n\r\n").await?;
+/// ```
+///
+/// # Notes
+/// For the Debug trait a dummy implementation is provided.
+pub struct AsyncTlsSocket<'a, S: SocketState = NotReady> {
+    /// The TLS connection
+    tls_connection: TlsConnection<'a, TcpSocket<Connected>, Aes128GcmSha256>,
+    /// The TLS config
+    tls_config: TlsConfig<'a, Aes128GcmSha256>,
+    /// The file descriptor of the underlying socket
+    fd: SocketFileDescriptor,
+    /// marker for the socket state
+    _marker: core::marker::PhantomData<S>,
+}
+
+impl<S: SocketState> Debug for AsyncTlsSocket<'_, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AsyncTlsSocket").finish()
+    }
+}
+
+impl<'a> AsyncTlsSocket<'a> {
+    /// Create a new async TLS socket.
+    ///
+    /// # Parameters
+    /// - `socket`: The TCP socket to use for the TLS connection
+    /// - `record_read_buf`: A buffer to use for reading records
+    /// - `record_write_buf`: A buffer to use for writing records
+    ///
+    /// # Returns
+    /// A new async TLS socket in the [`NotReady`] state. Use
+    /// [`AsyncTlsSocket::open()`] to get a ready socket.
+    pub fn new(
+        socket: TcpSocket<Connected>,
+        record_read_buf: &'a mut [u8],
+        record_write_buf: &'a mut [u8],
+    ) -> AsyncTlsSocket<'a, NotReady> {
+        let fd = socket.fd.clone();
+        AsyncTlsSocket {
+            tls_connection: TlsConnection::new(socket, record_read_buf, record_write_buf),
+            tls_config: TlsConfig::new(),
+            fd,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Open the TLS connection, performing the handshake.
+    ///
+    /// # Parameters
+    /// - `options`: The TLS options, of type [`TlsSocketOptions`].
+    ///
+    /// # Errors
+    /// [`TlsError`] if the handshake fails.
+    pub async fn open(
+        self,
+        options: &'a TlsSocketOptions<'a>,
+    ) -> Result<AsyncTlsSocket<'a, Ready>, TlsError> {
+        let mut rng = ChaCha20Rng::seed_from_u64(options.seed());
+
+        let mut tls_socket: AsyncTlsSocket<Ready> = AsyncTlsSocket {
+            tls_connection: self.tls_connection,
+            tls_config: configure(self.tls_config, options),
+            fd: self.fd,
+            _marker: core::marker::PhantomData,
+        };
+
+        let tls_context = TlsContext::new(&tls_socket.tls_config, &mut rng);
+        tls_socket
+            .tls_connection
+            .open::<ChaCha20Rng, NoVerify>(tls_context)
+            .await?;
+
+        Ok(tls_socket)
+    }
+}
+
+impl<S: SocketState> AsyncTlsSocket<'_, S> {
+    /// The local address of the underlying socket
+    ///
+    /// # Errors
+    /// - [`TlsError`] if the address could not be retrieved
+    pub fn local_addr(&self) -> Result<SocketAddr, TlsError> {
+        self.fd.local_addr().map_err(|e| to_tls_error(&e))
+    }
+
+    /// The address of the peer the underlying socket is connected to
+    ///
+    /// # Errors
+    /// - [`TlsError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, TlsError> {
+        self.fd.peer_addr().map_err(|e| to_tls_error(&e))
+    }
+
+    /// Close the TLS connection and the underlying socket.
+    ///
+    /// A `close_notify` alert is sent to the peer before closing the socket.
+    ///
+    /// # Errors
+    /// - [`TlsError`] if the alert could not be sent, or the socket could not
+    ///   be closed. The socket is closed in either case.
+    ///
+    /// # Notes
+    /// Only the last handle to the underlying descriptor closes it, see
+    /// [`TlsSocket::close`](super::TlsSocket::close).
+    pub async fn close(self) -> Result<(), TlsError> {
+        // release our handle first, so that closing the socket closes the descriptor
+        drop(self.fd);
+        match self.tls_connection.close().await {
+            Ok(socket) => socket.close().map_err(|e| to_tls_error(&e)),
+            Err((socket, error)) => {
+                drop(socket);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<S: SocketState> ErrorType for AsyncTlsSocket<'_, S> {
+    /// The error type for the TLS socket.
+    type Error = TlsError;
+}
+
+impl embedded_io_async::Read for AsyncTlsSocket<'_, Ready> {
+    /// Read data from the TLS connection.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the read was successful. The number of bytes read
+    /// - `Err(TlsError)` if the read was unsuccessful.
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.tls_connection.read(buf).await
+    }
+}
+
+impl embedded_io_async::Write for AsyncTlsSocket<'_, Ready> {
+    /// Write data to the TLS connection.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the write was successful. The number of bytes written
+    /// - `Err(TlsError)` if the write was unsuccessful.
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.tls_connection.write(buf).await
+    }
+
+    /// Flush the TLS connection.
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.tls_connection.flush().await
+    }
+}
+
+impl Pollable for AsyncTlsSocket<'_, Ready> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
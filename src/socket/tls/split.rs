@@ -0,0 +1,184 @@
+use alloc::sync::Arc;
+use core::fmt::Debug;
+
+use embedded_io::{ErrorType, Read, Write};
+use embedded_tls::{
+    blocking::{SplitConnectionState, TlsConnection, TlsReader, TlsWriter},
+    Aes128GcmSha256, TlsConfig, TlsError,
+};
+
+use super::super::{poll::Pollable, sce::SocketFileDescriptor, state::Ready, SocketAddr};
+use super::{to_tls_error, TlsSocket, Transport};
+
+/// The reading half of a [`TlsSocket`]
+///
+/// Created by [`TlsSocket::split`].
+///
+/// # Notes
+/// For the Debug trait a dummy implementation is provided.
+pub struct TlsReadHalf<'a> {
+    /// The reading half of the TLS connection
+    reader: TlsReader<'a, Transport, Aes128GcmSha256, &'a SplitConnectionState>,
+    /// The file descriptor of the underlying socket
+    fd: SocketFileDescriptor,
+}
+
+/// The writing half of a [`TlsSocket`]
+///
+/// Created by [`TlsSocket::split`].
+///
+/// # Notes
+/// For the Debug trait a dummy implementation is provided.
+pub struct TlsWriteHalf<'a> {
+    /// The writing half of the TLS connection
+    writer: TlsWriter<'a, Transport, Aes128GcmSha256, &'a SplitConnectionState>,
+    /// The file descriptor of the underlying socket
+    fd: SocketFileDescriptor,
+}
+
+/// Error returned by [`TlsSocket::unsplit`] when the halves do not come from
+/// the same TLS socket
+///
+/// It gives the halves back.
+pub struct TlsReuniteError<'a>(pub TlsReadHalf<'a>, pub TlsWriteHalf<'a>);
+
+impl Debug for TlsReuniteError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("TlsReuniteError").finish_non_exhaustive()
+    }
+}
+
+impl Debug for TlsReadHalf<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TlsReadHalf").finish()
+    }
+}
+
+impl Debug for TlsWriteHalf<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TlsWriteHalf").finish()
+    }
+}
+
+impl<'a> TlsSocket<'a, Ready> {
+    /// Split the TLS connection into a reading half and a writing half
+    ///
+    /// Each half owns a handle to the underlying socket, and its own record
+    /// buffer: they can be used at the same time, from different threads.
+    ///
+    /// # Parameters
+    /// - `state`: The state shared by the halves, recording whether the
+    ///   connection is still open
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut state = SplitConnectionState::default();
+    /// let (mut reader, mut writer) = tls_socket.split(&mut state);
+    /// std::thread::scope(|s| {
+    ///     s.spawn(move || writer.write_all(b"input"));
+    ///     reader.read(&mut buf)
+    /// });
+    /// ```
+    pub fn split(self, state: &'a mut SplitConnectionState) -> (TlsReadHalf<'a>, TlsWriteHalf<'a>) {
+        let (reader, writer) = self.tls_connection.split_with(state);
+        (
+            TlsReadHalf {
+                reader,
+                fd: self.fd.clone(),
+            },
+            TlsWriteHalf {
+                writer,
+                fd: self.fd,
+            },
+        )
+    }
+
+    /// Put the halves of a TLS connection back together
+    ///
+    /// # Errors
+    /// - [`TlsReuniteError`] if the halves do not come from the same
+    ///   [`TlsSocket::split`] call
+    // the halves are given back as they are, like the socket on success
+    #[allow(clippy::result_large_err)]
+    pub fn unsplit(
+        read: TlsReadHalf<'a>,
+        write: TlsWriteHalf<'a>,
+    ) -> Result<Self, TlsReuniteError<'a>> {
+        if !Arc::ptr_eq(&read.fd.0, &write.fd.0) {
+            return Err(TlsReuniteError(read, write));
+        }
+        Ok(TlsSocket {
+            tls_connection: TlsConnection::unsplit(read.reader, write.writer),
+            tls_config: TlsConfig::new(),
+            fd: write.fd,
+            _marker: core::marker::PhantomData,
+        })
+    }
+}
+
+impl TlsReadHalf<'_> {
+    /// The address of the peer the underlying socket is connected to
+    ///
+    /// # Errors
+    /// - [`TlsError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, TlsError> {
+        self.fd.peer_addr().map_err(|e| to_tls_error(&e))
+    }
+}
+
+impl TlsWriteHalf<'_> {
+    /// The address of the peer the underlying socket is connected to
+    ///
+    /// # Errors
+    /// - [`TlsError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, TlsError> {
+        self.fd.peer_addr().map_err(|e| to_tls_error(&e))
+    }
+}
+
+impl ErrorType for TlsReadHalf<'_> {
+    type Error = TlsError;
+}
+
+impl ErrorType for TlsWriteHalf<'_> {
+    type Error = TlsError;
+}
+
+impl Read for TlsReadHalf<'_> {
+    /// Read data from the TLS connection.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the read was successful. The number of bytes read
+    /// - `Err(TlsError)` if the read was unsuccessful.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.reader.read(buf)
+    }
+}
+
+impl Write for TlsWriteHalf<'_> {
+    /// Write data to the TLS connection.
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the write was successful. The number of bytes written
+    /// - `Err(TlsError)` if the write was unsuccessful.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.writer.write(buf)
+    }
+
+    /// Flush the TLS connection.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush()
+    }
+}
+
+impl Pollable for TlsReadHalf<'_> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
+
+impl Pollable for TlsWriteHalf<'_> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
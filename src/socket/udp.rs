@@ -1,23 +1,26 @@
 #![allow(clippy::module_name_repetitions)]
 
-use alloc::{borrow::ToOwned, vec::Vec};
-use core::net::{IpAddr, Ipv4Addr, SocketAddr};
-use embedded_io::{ErrorType, Read, Write};
-use psp::sys::{self, sockaddr, socklen_t};
-
-use core::ffi::c_void;
-
 use crate::{
+    backend::{DefaultBackend, NetBackend},
+    netc::sockaddr,
     traits::{
         io::{EasySocket, Open, OptionType},
         SocketBuffer,
     },
     types::{SocketOptions, SocketRecvFlags, SocketSendFlags},
 };
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+use embedded_io::{ErrorType, Read, ReadReady, Write, WriteReady};
 
 use super::{
     super::netc,
     error::SocketError,
+    option::{
+        AddMembership, Broadcast, DropMembership, GetSocketOption, MulticastLoop, MulticastTtl,
+        NonBlocking, SetSocketOption,
+    },
+    poll::{self, Interest, Pollable},
     sce::SocketFileDescriptor,
     state::{Bound, Connected, SocketState, Unbound},
     ToSockaddr, ToSocketAddr,
@@ -33,8 +36,9 @@ use super::{
 ///   This method return a [`UdpSocket`] already connected, and ready to send/receive data (using the
 ///   [`write`](embedded_io::Write::write) and [`read`](embedded_io::Read::read) methods).
 /// - The socket is closed when the struct is dropped. Closing via drop is best-effort.
+/// - The struct is not [`Clone`]: [`UdpSocket::try_clone`] creates another handle to the
+///   socket, sharing its file descriptor.
 #[repr(C)]
-#[derive(Clone)]
 pub struct UdpSocket<S: SocketState = Unbound, B: SocketBuffer = Vec<u8>> {
     /// The socket file descriptor
     fd: SocketFileDescriptor,
@@ -50,6 +54,27 @@ pub struct UdpSocket<S: SocketState = Unbound, B: SocketBuffer = Vec<u8>> {
     _marker: core::marker::PhantomData<S>,
 }
 
+/// The broadcast address of the subnet `addr` belongs to
+///
+/// Datagrams sent to it reach every host of the subnet, provided broadcasting
+/// is enabled on the socket (see [`UdpSocket::set_broadcast`]).
+///
+/// # Example
+/// ```
+/// use core::net::Ipv4Addr;
+/// use psp_net::socket::udp::broadcast_address;
+///
+/// let broadcast = broadcast_address(
+///     Ipv4Addr::new(192, 168, 1, 42),
+///     Ipv4Addr::new(255, 255, 255, 0),
+/// );
+/// assert_eq!(broadcast, Ipv4Addr::new(192, 168, 1, 255));
+/// ```
+#[must_use]
+pub fn broadcast_address(addr: Ipv4Addr, netmask: Ipv4Addr) -> Ipv4Addr {
+    Ipv4Addr::from(u32::from(addr) | !u32::from(netmask))
+}
+
 impl UdpSocket {
     /// Create a socket
     ///
@@ -62,40 +87,263 @@ impl UdpSocket {
     /// - [`SocketError::Errno`] if the socket could not be created
     #[allow(dead_code)]
     pub fn new() -> Result<UdpSocket<Unbound>, SocketError> {
-        let fd = unsafe { sys::sceNetInetSocket(i32::from(netc::AF_INET), netc::SOCK_DGRAM, 0) };
-        if fd < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
-        } else {
-            let fd = SocketFileDescriptor::new(fd);
-            Ok(UdpSocket {
-                fd,
-                remote: None,
-                buffer: Vec::with_capacity(0),
-                send_flags: SocketSendFlags::empty(),
-                recv_flags: SocketRecvFlags::empty(),
-                _marker: core::marker::PhantomData,
-            })
-        }
+        Self::with_buffer(Vec::with_capacity(0))
     }
 }
 
-impl<S: SocketState> UdpSocket<S> {
-    fn socket_len() -> socklen_t {
-        core::mem::size_of::<netc::sockaddr>() as u32
-    }
-
+impl<S: SocketState, B: SocketBuffer> UdpSocket<S, B> {
     /// Get the file descriptor of the socket
     #[must_use]
     pub fn fd(&self) -> i32 {
         *self.fd
     }
 
+    /// Wrap a socket file descriptor created by other code (e.g. a C library)
+    ///
+    /// The socket takes ownership of the descriptor, and closes it when
+    /// dropped, unless it is released with [`into_raw_fd`](Self::into_raw_fd).
+    /// It starts with a new send buffer, no send nor receive flags, and no
+    /// [`remote`](Self::remote) address.
+    ///
+    /// # Safety
+    /// - `fd` must be an open UDP socket file descriptor, in the state `S`
+    /// - `fd` must not be closed by other code while the socket exists, nor
+    ///   wrapped more than once
+    #[must_use]
+    pub unsafe fn from_raw_fd(fd: i32) -> Self {
+        UdpSocket {
+            fd: SocketFileDescriptor::new(fd),
+            remote: None,
+            buffer: B::new(),
+            send_flags: SocketSendFlags::empty(),
+            recv_flags: SocketRecvFlags::empty(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Release the socket's file descriptor
+    ///
+    /// The descriptor is no longer closed when the socket is dropped: closing
+    /// it is up to the caller. Data still in the send buffer is discarded:
+    /// flush the socket first to send it.
+    ///
+    /// # Errors
+    /// The socket itself, if other handles share its descriptor (see
+    /// [`try_clone`](Self::try_clone)), as they would still close it.
+    pub fn into_raw_fd(self) -> Result<i32, Self> {
+        let UdpSocket {
+            fd,
+            remote,
+            buffer,
+            send_flags,
+            recv_flags,
+            _marker: _,
+        } = self;
+        fd.into_raw().map_err(|fd| UdpSocket {
+            fd,
+            remote,
+            buffer,
+            send_flags,
+            recv_flags,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    /// Create a new handle to the socket
+    ///
+    /// The handles share the socket's file descriptor, which is closed once all
+    /// of them are dropped. The new handle has the same flags and remote
+    /// address, and an empty send buffer.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the descriptor is no longer valid (e.g. it was
+    ///   closed by other code)
+    pub fn try_clone(&self) -> Result<Self, SocketError> {
+        self.fd.local_addr()?;
+        let mut buffer = self.buffer.clone();
+        buffer.clear();
+        Ok(UdpSocket {
+            fd: self.fd.clone(),
+            remote: self.remote,
+            buffer,
+            send_flags: self.send_flags,
+            recv_flags: self.recv_flags,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    /// The local address of the socket
+    ///
+    /// Useful to learn the port assigned to a socket bound to port `0`.
+    /// An unbound socket reports the unspecified address and port `0`.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn local_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.local_addr()
+    }
+
+    /// Close the socket
+    ///
+    /// Unlike dropping the socket, this reports whether closing it failed.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be closed
+    ///
+    /// # Notes
+    /// Only the last handle to the descriptor closes it. If other handles exist
+    /// (see [`UdpSocket::try_clone`], or a [`Poller`](super::poll::Poller) the
+    /// socket is registered in), this handle is released and `Ok(())` is
+    /// returned: the socket is closed when the last handle is dropped.
+    pub fn close(self) -> Result<(), SocketError> {
+        self.fd.close()
+    }
+
     /// Get the remote address of the socket
+    ///
+    /// This is the address the socket was connected to, as passed in. Use
+    /// [`UdpSocket::peer_addr`] to query the network stack instead.
     #[must_use]
     pub fn remote(&self) -> Option<SocketAddr> {
         self.remote.map(|sockaddr| sockaddr.to_socket_addr())
     }
 
+    /// Move the socket into or out of non-blocking mode
+    ///
+    /// In non-blocking mode, operations that cannot complete immediately fail
+    /// with [`SocketError::WouldBlock`] instead of blocking the calling thread.
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the mode could not be set
+    pub fn set_nonblocking(&self, nonblocking: bool) -> Result<(), SocketError> {
+        self.fd.set_option::<NonBlocking>(nonblocking)
+    }
+
+    /// Allow or forbid sending datagrams to broadcast addresses
+    ///
+    /// Broadcasting is disabled by default. See [`broadcast_address`] to compute
+    /// the broadcast address of a subnet.
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be set
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), SocketError> {
+        self.fd.set_option::<Broadcast>(broadcast)
+    }
+
+    /// Whether sending datagrams to broadcast addresses is allowed
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be read
+    pub fn broadcast(&self) -> Result<bool, SocketError> {
+        self.fd.option::<Broadcast>()
+    }
+
+    /// Join a multicast group
+    ///
+    /// Datagrams sent to `group` are received by the socket, as long as it is
+    /// bound to the port they are sent to.
+    ///
+    /// # Parameters
+    /// - `group`: The address of the multicast group
+    /// - `interface`: The address of the local interface to join the group on.
+    ///   [`Ipv4Addr::UNSPECIFIED`] lets the network stack choose.
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the group could not be joined
+    pub fn join_multicast_v4(
+        &self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), SocketError> {
+        self.fd.set_option::<AddMembership>((group, interface))
+    }
+
+    /// Leave a multicast group
+    ///
+    /// # Parameters
+    /// - `group`: The address of the multicast group
+    /// - `interface`: The address of the local interface the group was joined on
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the group could not be left
+    pub fn leave_multicast_v4(
+        &self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), SocketError> {
+        self.fd.set_option::<DropMembership>((group, interface))
+    }
+
+    /// Set the time to live of outgoing multicast datagrams
+    ///
+    /// The default of `1` keeps multicast datagrams in the local network.
+    /// Values above `255` are clamped.
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be set
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> Result<(), SocketError> {
+        self.fd.set_option::<MulticastTtl>(ttl)
+    }
+
+    /// The time to live of outgoing multicast datagrams
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be read
+    pub fn multicast_ttl_v4(&self) -> Result<u32, SocketError> {
+        self.fd.option::<MulticastTtl>()
+    }
+
+    /// Set whether outgoing multicast datagrams are looped back to the local host
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be set
+    pub fn set_multicast_loop_v4(&self, multicast_loop: bool) -> Result<(), SocketError> {
+        self.fd.set_option::<MulticastLoop>(multicast_loop)
+    }
+
+    /// Whether outgoing multicast datagrams are looped back to the local host
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the option could not be read
+    pub fn multicast_loop_v4(&self) -> Result<bool, SocketError> {
+        self.fd.option::<MulticastLoop>()
+    }
+
+    /// Set a socket option
+    ///
+    /// See the [`option`](super::option) module for the supported options.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the option could not be set
+    pub fn set_option<O: SetSocketOption>(&self, value: O::Value) -> Result<(), SocketError> {
+        self.fd.set_option::<O>(value)
+    }
+
+    /// Get the value of a socket option
+    ///
+    /// See the [`option`](super::option) module for the supported options.
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the option could not be read
+    pub fn option<O: GetSocketOption>(&self) -> Result<O::Value, SocketError> {
+        self.fd.option::<O>()
+    }
+
+    /// Run `op` with `MSG_DONTWAIT` added to the send and receive flags
+    ///
+    /// Async operations must not block the executor, whatever the blocking
+    /// mode of the socket.
+    #[cfg(feature = "async")]
+    fn dont_wait<T>(&mut self, op: impl FnOnce(&mut Self) -> T) -> T {
+        let (send_flags, recv_flags) = (self.send_flags, self.recv_flags);
+        self.send_flags |= SocketSendFlags::MSG_DONTWAIT;
+        self.recv_flags |= SocketRecvFlags::MSG_DONTWAIT;
+        let result = op(self);
+        self.send_flags = send_flags;
+        self.recv_flags = recv_flags;
+        result
+    }
+
     /// Flags used when sending data
     #[must_use]
     pub fn send_flags(&self) -> SocketSendFlags {
@@ -119,13 +367,44 @@ impl<S: SocketState> UdpSocket<S> {
     }
 }
 
-impl UdpSocket<Unbound> {
+impl<B: SocketBuffer> UdpSocket<Unbound, B> {
+    /// Create a socket, buffering the data to send in `buffer`
+    ///
+    /// The buffer is kept through every state transition. See [`UdpSocket::new`].
+    ///
+    /// # Example
+    /// ```ignore
+    /// use psp_net::types::RingBuffer;
+    ///
+    /// let socket = UdpSocket::with_buffer(RingBuffer::with_capacity(1472))?;
+    /// let socket = socket.open(&SocketOptions::new(addr))?;
+    /// ```
+    ///
+    /// # Errors
+    /// - [`SocketError::Errno`] if the socket could not be created
+    pub fn with_buffer(buffer: B) -> Result<Self, SocketError> {
+        let fd = DefaultBackend::socket(i32::from(netc::AF_INET), netc::SOCK_DGRAM, 0);
+        if fd < 0 {
+            Err(SocketError::from_errno(DefaultBackend::errno()))
+        } else {
+            let fd = SocketFileDescriptor::new(fd);
+            Ok(UdpSocket {
+                fd,
+                remote: None,
+                buffer,
+                send_flags: SocketSendFlags::empty(),
+                recv_flags: SocketRecvFlags::empty(),
+                _marker: core::marker::PhantomData,
+            })
+        }
+    }
+
     /// Transition the socket to `Bound` state
-    fn transition(self, remote: Option<sockaddr>) -> UdpSocket<Bound> {
+    fn transition(self, remote: Option<sockaddr>) -> UdpSocket<Bound, B> {
         UdpSocket {
             fd: self.fd,
             remote,
-            buffer: Vec::with_capacity(0),
+            buffer: self.buffer,
             send_flags: self.send_flags,
             recv_flags: self.recv_flags,
             _marker: core::marker::PhantomData,
@@ -144,23 +423,16 @@ impl UdpSocket<Unbound> {
     /// # Errors
     /// - [`SocketError::Errno`] if the binding was unsuccessful
     #[allow(unused)]
-    pub fn bind(mut self, addr: Option<SocketAddr>) -> Result<UdpSocket<Bound>, SocketError> {
+    pub fn bind(mut self, addr: Option<SocketAddr>) -> Result<UdpSocket<Bound, B>, SocketError> {
         let default_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 0);
         let addr = addr.unwrap_or(default_addr);
         match addr {
             SocketAddr::V4(v4) => {
                 let sockaddr = v4.to_sockaddr();
 
-                if unsafe {
-                    sys::sceNetInetBind(
-                        *self.fd,
-                        &sockaddr,
-                        core::mem::size_of::<netc::sockaddr>() as u32,
-                    )
-                } != 0
-                {
-                    let errno = unsafe { sys::sceNetInetGetErrno() };
-                    Err(SocketError::Errno(errno))
+                if DefaultBackend::bind(*self.fd, &sockaddr) != 0 {
+                    let errno = DefaultBackend::errno();
+                    Err(SocketError::from_errno(errno))
                 } else {
                     Ok(self.transition(Some(sockaddr)))
                 }
@@ -170,13 +442,13 @@ impl UdpSocket<Unbound> {
     }
 }
 
-impl UdpSocket<Bound> {
+impl<B: SocketBuffer> UdpSocket<Bound, B> {
     /// Transition the socket to `Connected` state
-    fn transition(self, remote: sockaddr, buf: Option<Vec<u8>>) -> UdpSocket<Connected> {
+    fn transition(self, remote: sockaddr) -> UdpSocket<Connected, B> {
         UdpSocket {
             fd: self.fd,
             remote: Some(remote),
-            buffer: buf.unwrap_or_default(),
+            buffer: self.buffer,
             send_flags: self.send_flags,
             recv_flags: self.recv_flags,
             _marker: core::marker::PhantomData,
@@ -196,22 +468,78 @@ impl UdpSocket<Bound> {
     /// # Errors
     /// - Any [`SocketError`] if the connection was unsuccessful
     #[allow(unused)]
-    pub fn connect(mut self, addr: SocketAddr) -> Result<UdpSocket<Connected>, SocketError> {
+    pub fn connect(mut self, addr: SocketAddr) -> Result<UdpSocket<Connected, B>, SocketError> {
         match addr {
             SocketAddr::V4(v4) => {
                 let sockaddr = v4.to_sockaddr();
 
-                if unsafe { sys::sceNetInetConnect(*self.fd, &sockaddr, Self::socket_len()) } != 0 {
-                    let errno = unsafe { sys::sceNetInetGetErrno() };
-                    Err(SocketError::Errno(errno))
+                if DefaultBackend::connect(*self.fd, &sockaddr) != 0 {
+                    let errno = DefaultBackend::errno();
+                    Err(SocketError::from_errno(errno))
                 } else {
-                    Ok(self.transition(sockaddr, None))
+                    Ok(self.transition(sockaddr))
                 }
             }
             SocketAddr::V6(_) => Err(SocketError::UnsupportedAddressFamily),
         }
     }
 
+    /// Send a datagram to `to`
+    ///
+    /// The socket stays bound, so it can be used to talk to many peers.
+    ///
+    /// # Parameters
+    /// - `buf`: The data to send
+    /// - `to`: The address to send the data to
+    ///
+    /// # Returns
+    /// - `Ok(usize)` if the send was successful. The number of bytes sent
+    /// - `Err(SocketError)` if the send was unsuccessful.
+    ///
+    /// # Errors
+    /// - [`SocketError::UnsupportedAddressFamily`] if `to` is not an IPv4 address
+    /// - Any [`SocketError`] if the send was unsuccessful
+    pub fn send_to(&self, buf: &[u8], to: SocketAddr) -> Result<usize, SocketError> {
+        let SocketAddr::V4(v4) = to else {
+            return Err(SocketError::UnsupportedAddressFamily);
+        };
+        let result =
+            DefaultBackend::send_to(*self.fd, buf, self.send_flags.as_i32(), &v4.to_sockaddr());
+        if result < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok(result as usize)
+        }
+    }
+
+    /// Receive a datagram, from any peer
+    ///
+    /// The socket stays bound, so it can be used to talk to many peers.
+    ///
+    /// # Parameters
+    /// - `buf`: The buffer where to store the received data. If it is smaller
+    ///   than the datagram, the excess bytes are discarded.
+    ///
+    /// # Returns
+    /// - `Ok((usize, SocketAddr))` if the read was successful. The number of
+    ///   bytes read, and the address of the sender
+    /// - `Err(SocketError)` if the read was unsuccessful.
+    ///
+    /// # Errors
+    /// - Any [`SocketError`] if the read was unsuccessful
+    pub fn recv_from(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr), SocketError> {
+        let mut sockaddr: sockaddr = unsafe { core::mem::zeroed() };
+        let result =
+            DefaultBackend::recv_from(*self.fd, buf, self.recv_flags.as_i32(), &mut sockaddr);
+        if result < 0 {
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
+        } else {
+            Ok((result as usize, sockaddr.to_socket_addr()))
+        }
+    }
+
     /// Read from a bound socket
     ///
     /// # Parameters
@@ -224,27 +552,21 @@ impl UdpSocket<Bound> {
     /// # Errors
     /// - Any [`SocketError`] if the read was unsuccessful
     #[allow(unused)]
+    #[deprecated(note = "use `recv_from`, which leaves the socket bound")]
     pub fn _read_from(
         mut self,
         buf: &mut [u8],
-    ) -> Result<(usize, UdpSocket<Connected>), SocketError> {
+    ) -> Result<(usize, UdpSocket<Connected, B>), SocketError> {
         let mut sockaddr = self
             .remote
             .ok_or(SocketError::Other("Remote not set".to_owned()))?;
-        let result = unsafe {
-            sys::sceNetInetRecvfrom(
-                *self.fd,
-                buf.as_mut_ptr().cast::<c_void>(),
-                buf.len(),
-                self.recv_flags.as_i32(),
-                &mut sockaddr,
-                &mut Self::socket_len(),
-            )
-        };
+        let result =
+            DefaultBackend::recv_from(*self.fd, buf, self.recv_flags.as_i32(), &mut sockaddr);
         if result < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
         } else {
-            Ok((result as usize, self.transition(sockaddr, None)))
+            Ok((result as usize, self.transition(sockaddr)))
         }
     }
 
@@ -252,7 +574,8 @@ impl UdpSocket<Bound> {
     ///
     /// # Parameters
     /// - `buf`: The buffer containing the data to send
-    ///
+    /// - `len`: The number of bytes of `buf` to send
+    /// - `to`: The address to send the data to
     ///
     /// # Returns
     /// - `Ok((usize, UdpSocket<Connected>))` if the send was successful. The number of bytes sent
@@ -261,41 +584,67 @@ impl UdpSocket<Bound> {
     /// # Errors
     /// - Any [`SocketError`] if the send was unsuccessful
     #[allow(unused)]
+    #[deprecated(note = "use `send_to`, which leaves the socket bound")]
     pub fn _write_to(
         self,
         buf: &[u8],
         len: usize,
         to: SocketAddr,
-    ) -> Result<(usize, UdpSocket<Connected>), SocketError> {
-        let sockaddr = match to {
-            SocketAddr::V4(v4) => Ok(super::socket_addr_v4_to_sockaddr(v4)),
-            SocketAddr::V6(_) => Err(SocketError::UnsupportedAddressFamily),
-        }?;
-        let socklen = core::mem::size_of::<netc::sockaddr>() as u32;
-
-        let mut buffer = Vec::with_capacity(buf.len());
-        buffer.append_buffer(buf);
-
-        let result = unsafe {
-            sys::sceNetInetSendto(
-                *self.fd,
-                buf.as_ptr().cast::<c_void>(),
-                len,
-                self.send_flags.as_i32(),
-                &sockaddr,
-                socklen,
-            )
+    ) -> Result<(usize, UdpSocket<Connected, B>), SocketError> {
+        let sent = self.send_to(&buf[..len.min(buf.len())], to)?;
+        let SocketAddr::V4(v4) = to else {
+            unreachable!("send_to only accepts IPv4 addresses");
         };
-        if result < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
-        } else {
-            buffer.shift_left_buffer(result as usize);
-            Ok((result as usize, self.transition(sockaddr, Some(buffer))))
-        }
+        Ok((sent, self.transition(v4.to_sockaddr())))
     }
 }
 
-impl UdpSocket<Connected> {
+impl<B: SocketBuffer> UdpSocket<Connected, B> {
+    /// The address of the peer the socket is connected to
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the address could not be retrieved
+    pub fn peer_addr(&self) -> Result<SocketAddr, SocketError> {
+        self.fd.peer_addr()
+    }
+
+    /// Dissolve the association with the remote host
+    ///
+    /// The send buffer is flushed first. The socket goes back to the
+    /// [`Bound`] state, and can again send to and receive from any peer.
+    ///
+    /// # Notes
+    /// BSD-derived stacks, such as the PSP's, report `EAFNOSUPPORT` once the
+    /// association is dissolved: the backend treats it as a success.
+    ///
+    /// The socket keeps its local address, with one exception: some network
+    /// stacks (e.g. Linux) release a port that was assigned automatically. Bind
+    /// to an explicit port if it must survive the disconnection.
+    ///
+    /// # Errors
+    /// - Any [`SocketError`] if the send buffer could not be flushed, or the
+    ///   socket could not be disconnected
+    pub fn disconnect(mut self) -> Result<UdpSocket<Bound, B>, SocketError> {
+        self.internal_flush()?;
+
+        let mut sockaddr: sockaddr = unsafe { core::mem::zeroed() };
+        sockaddr.sa_len = core::mem::size_of::<sockaddr>() as u8;
+        sockaddr.sa_family = netc::AF_UNSPEC;
+        if DefaultBackend::connect(*self.fd, &sockaddr) != 0 {
+            let errno = DefaultBackend::errno();
+            return Err(SocketError::from_errno(errno));
+        }
+
+        Ok(UdpSocket {
+            fd: self.fd,
+            remote: None,
+            buffer: self.buffer,
+            send_flags: self.send_flags,
+            recv_flags: self.recv_flags,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
     /// Read from a socket
     ///
     /// # Parameters
@@ -309,16 +658,10 @@ impl UdpSocket<Connected> {
     /// - Any [`SocketError`] if the read was unsuccessful
     #[allow(unused)]
     pub fn internal_read(&mut self, buf: &mut [u8]) -> Result<usize, SocketError> {
-        let result = unsafe {
-            sys::sceNetInetRecv(
-                *self.fd,
-                buf.as_mut_ptr().cast::<c_void>(),
-                buf.len(),
-                self.recv_flags.as_i32(),
-            )
-        };
+        let result = DefaultBackend::recv(*self.fd, buf, self.recv_flags.as_i32());
         if result < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
         } else {
             Ok(result as usize)
         }
@@ -326,16 +669,35 @@ impl UdpSocket<Connected> {
 
     /// Write to a socket
     ///
+    /// `buf` is a single datagram, written whole or not at all. The datagram
+    /// pending in the send buffer, if any, is sent first. Then `buf` is sent,
+    /// or buffered whole if the socket would block and the send buffer has
+    /// room for it.
+    ///
     /// # Returns
-    /// - `Ok(usize)` if the send was successful. The number of bytes sent
+    /// - `Ok(usize)` if the write was successful. The length of `buf`
     /// - `Err(SocketError)` if the send was unsuccessful.
     ///
     /// # Errors
+    /// - [`SocketError::WouldBlock`] if the socket is non-blocking, and a
+    ///   datagram is still pending or `buf` does not fit in the send buffer
+    /// - [`SocketError::MessageTooLong`] if `buf` is larger than a datagram
     /// - Any [`SocketError`] if the send was unsuccessful
     #[allow(unused)]
     pub fn internal_write(&mut self, buf: &[u8]) -> Result<usize, SocketError> {
-        self.buffer.append_buffer(buf);
-        self.send()
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // the pending datagram goes out first, to keep the datagrams in order
+        self.internal_flush()?;
+
+        match self.send_slice(buf) {
+            Err(SocketError::WouldBlock) if buf.len() <= self.buffer.spare_capacity() => {
+                self.buffer.append_buffer(buf);
+                Ok(buf.len())
+            }
+            result => result,
+        }
     }
 
     /// Flush the send buffer
@@ -343,40 +705,45 @@ impl UdpSocket<Connected> {
     /// # Errors
     /// - Any [`SocketError`] if the flush was unsuccessful.
     pub fn internal_flush(&mut self) -> Result<(), SocketError> {
-        while !self.buffer.is_empty() {
+        if !self.buffer.is_empty() {
             self.send()?;
         }
         Ok(())
     }
 
+    /// Send the datagram of the send buffer
     fn send(&mut self) -> Result<usize, SocketError> {
-        let result = unsafe {
-            sys::sceNetInetSend(
-                *self.fd,
-                self.buffer.as_slice().as_ptr().cast::<c_void>(),
-                self.buffer.len(),
-                self.send_flags.as_i32(),
-            )
-        };
+        // a ring buffer may hold the data in two parts, which must not be
+        // sent as two datagrams
+        self.buffer.make_contiguous();
+        let sent = self.send_slice(self.buffer.as_slice())?;
+        // a datagram is sent whole
+        self.buffer.clear();
+        Ok(sent)
+    }
+
+    /// Send `buf`, bypassing the send buffer
+    fn send_slice(&self, buf: &[u8]) -> Result<usize, SocketError> {
+        let result = DefaultBackend::send(*self.fd, buf, self.send_flags.as_i32());
         if result < 0 {
-            Err(SocketError::Errno(unsafe { sys::sceNetInetGetErrno() }))
+            let errno = DefaultBackend::errno();
+            Err(SocketError::from_errno(errno))
         } else {
-            self.buffer.shift_left_buffer(result as usize);
             Ok(result as usize)
         }
     }
 }
 
-impl<S: SocketState> OptionType for UdpSocket<S> {
+impl<S: SocketState, B: SocketBuffer> OptionType for UdpSocket<S, B> {
     type Options<'a> = SocketOptions;
 }
 
-impl<S: SocketState> ErrorType for UdpSocket<S> {
+impl<S: SocketState, B: SocketBuffer> ErrorType for UdpSocket<S, B> {
     type Error = SocketError;
 }
 
-impl Open<'_, '_> for UdpSocket<Unbound> {
-    type Return = UdpSocket<Connected>;
+impl<B: SocketBuffer> Open<'_, '_> for UdpSocket<Unbound, B> {
+    type Return = UdpSocket<Connected, B>;
     /// Open the socket
     ///
     /// # Parameters
@@ -387,7 +754,7 @@ impl Open<'_, '_> for UdpSocket<Unbound> {
     /// - `Err(SocketError)` if the socket failed to open.
     ///
     /// # Examples
-    /// ```no_run
+    /// ```ignore
     /// let socket = UdpSocket::new()?;
     /// let socket = socket.open(&SocketOptions::default())?;
     /// ```
@@ -398,7 +765,7 @@ impl Open<'_, '_> for UdpSocket<Unbound> {
     }
 }
 
-impl Read for UdpSocket<Connected> {
+impl<B: SocketBuffer> Read for UdpSocket<Connected, B> {
     /// Read from the socket
     ///
     /// # Parameters
@@ -412,7 +779,7 @@ impl Read for UdpSocket<Connected> {
     }
 }
 
-impl Write for UdpSocket<Connected> {
+impl<B: SocketBuffer> Write for UdpSocket<Connected, B> {
     /// Write to the socket
     ///
     /// # Parameters
@@ -434,4 +801,284 @@ impl Write for UdpSocket<Connected> {
     }
 }
 
-impl EasySocket for UdpSocket<Connected> {}
+#[cfg(feature = "async")]
+impl<B: SocketBuffer> embedded_io_async::Read for UdpSocket<Connected, B> {
+    /// Read from the socket, without blocking the executor
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the read was unsuccessful
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        poll::retry(self.fd.clone(), Interest::READABLE, || {
+            self.dont_wait(|socket| socket.internal_read(buf))
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+impl<B: SocketBuffer> embedded_io_async::Write for UdpSocket<Connected, B> {
+    /// Write to the socket, without blocking the executor
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the write was unsuccessful
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        poll::retry(self.fd.clone(), Interest::WRITABLE, || {
+            self.dont_wait(|socket| socket.internal_write(buf))
+        })
+        .await
+    }
+
+    /// Flush the socket, without blocking the executor
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the flush was unsuccessful
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        poll::retry(self.fd.clone(), Interest::WRITABLE, || {
+            self.dont_wait(Self::internal_flush)
+        })
+        .await
+    }
+}
+
+impl<B: SocketBuffer> ReadReady for UdpSocket<Connected, B> {
+    /// Whether a datagram can be read without blocking
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be probed
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) = poll::probe(&self.fd, Interest::READABLE)?;
+        Ok(readiness.contains(Interest::READABLE))
+    }
+}
+
+impl<B: SocketBuffer> WriteReady for UdpSocket<Connected, B> {
+    /// Whether a datagram can be written without blocking
+    ///
+    /// # Errors
+    /// - A [`SocketError`] if the socket could not be probed
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        let (readiness, _) = poll::probe(&self.fd, Interest::WRITABLE)?;
+        Ok(readiness.contains(Interest::WRITABLE))
+    }
+}
+
+impl<B: SocketBuffer> Pollable for UdpSocket<Bound, B> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
+
+impl<B: SocketBuffer> Pollable for UdpSocket<Connected, B> {
+    fn socket_fd(&self) -> &SocketFileDescriptor {
+        &self.fd
+    }
+}
+
+impl<B: SocketBuffer> EasySocket for UdpSocket<Connected, B> {}
+
+#[cfg(all(test, feature = "host"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_and_echo() {
+        let server = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 64];
+            let (n, from) = server.recv_from(&mut buf).unwrap();
+            server.send_to(&buf[..n], from).unwrap();
+        });
+
+        let mut socket = UdpSocket::new()
+            .unwrap()
+            .open(&SocketOptions::new(addr))
+            .unwrap();
+        assert_eq!(socket.peer_addr().unwrap(), addr);
+        assert_ne!(socket.local_addr().unwrap().port(), 0);
+        socket.write_all(b"datagram").unwrap();
+        let mut buf = [0u8; 64];
+        let n = socket.read(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"datagram");
+        socket.close().unwrap();
+    }
+
+    #[test]
+    fn bound_socket_talks_to_many_peers() {
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        let peers = [
+            std::net::UdpSocket::bind("127.0.0.1:0").unwrap(),
+            std::net::UdpSocket::bind("127.0.0.1:0").unwrap(),
+        ];
+        for (i, peer) in peers.iter().enumerate() {
+            peer.send_to(&[i as u8], addr).unwrap();
+        }
+
+        let mut buf = [0u8; 8];
+        for _ in 0..peers.len() {
+            let (n, from) = socket.recv_from(&mut buf).unwrap();
+            assert_eq!(n, 1);
+            assert_eq!(peers[usize::from(buf[0])].local_addr().unwrap(), from);
+            socket.send_to(&[buf[0] + 10], from).unwrap();
+        }
+        for (i, peer) in peers.iter().enumerate() {
+            let n = peer.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..n], &[i as u8 + 10]);
+        }
+    }
+
+    #[test]
+    fn multicast_options_round_trip() {
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([0, 0, 0, 0], 0))))
+            .unwrap();
+
+        socket.set_multicast_ttl_v4(4).unwrap();
+        assert_eq!(socket.multicast_ttl_v4().unwrap(), 4);
+        socket.set_multicast_loop_v4(false).unwrap();
+        assert!(!socket.multicast_loop_v4().unwrap());
+        socket.set_multicast_loop_v4(true).unwrap();
+        assert!(socket.multicast_loop_v4().unwrap());
+
+        let group = Ipv4Addr::new(239, 255, 42, 99);
+        socket
+            .join_multicast_v4(group, Ipv4Addr::LOCALHOST)
+            .unwrap();
+        socket
+            .leave_multicast_v4(group, Ipv4Addr::LOCALHOST)
+            .unwrap();
+        assert!(socket
+            .leave_multicast_v4(group, Ipv4Addr::LOCALHOST)
+            .is_err());
+    }
+
+    #[test]
+    fn disconnect_accepts_any_peer_again() {
+        let first = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let second = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        // some hosts release an automatically assigned port on disconnect
+        let local = std::net::UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(local))
+            .unwrap()
+            .connect(first.local_addr().unwrap())
+            .unwrap();
+        let socket = socket.disconnect().unwrap();
+        assert!(socket.remote().is_none());
+
+        assert_eq!(socket.local_addr().unwrap(), local);
+        second.send_to(b"hello", local).unwrap();
+        let mut buf = [0u8; 8];
+        let (n, from) = socket.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(from, second.local_addr().unwrap());
+
+        socket.send_to(b"again", from).unwrap();
+        let (n, from) = second.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"again");
+        assert_eq!(from, local);
+    }
+
+    #[test]
+    fn writes_keep_datagram_boundaries() {
+        use crate::{backend::HostBackend, types::RingBuffer};
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut socket = UdpSocket::with_buffer(RingBuffer::with_capacity(16))
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap()
+            .connect(peer.local_addr().unwrap())
+            .unwrap();
+        socket.set_nonblocking(true).unwrap();
+
+        // the first datagram is buffered, the second must wait for it
+        HostBackend::block_sends(2);
+        assert_eq!(socket.write(b"first"), Ok(5));
+        assert_eq!(socket.write(b"second"), Err(SocketError::WouldBlock));
+        assert_eq!(socket.write(b"second"), Ok(6));
+
+        // larger than the send buffer: sent whole, never buffered in part
+        let large = [7u8; 64];
+        HostBackend::block_sends(1);
+        assert_eq!(socket.write(&large), Err(SocketError::WouldBlock));
+        assert_eq!(socket.write(&large), Ok(64));
+        assert_eq!(
+            socket.write(&alloc::vec![0u8; 70_000]),
+            Err(SocketError::MessageTooLong)
+        );
+        socket.flush().unwrap();
+
+        let mut buf = [0u8; 128];
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"first");
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"second");
+        let n = peer.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], &large[..]);
+        peer.set_nonblocking(true).unwrap();
+        assert!(peer.recv(&mut buf).is_err());
+    }
+
+    #[test]
+    fn try_clone_shares_the_descriptor() {
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap();
+        let local = socket.local_addr().unwrap();
+
+        let clone = socket.try_clone().unwrap();
+        clone.send_to(b"clone", peer.local_addr().unwrap()).unwrap();
+        let socket = socket.into_raw_fd().err().unwrap();
+        drop(clone);
+
+        let fd = socket.into_raw_fd().ok().unwrap();
+        let socket: UdpSocket<Bound> = unsafe { UdpSocket::from_raw_fd(fd) };
+        assert_eq!(socket.local_addr().unwrap(), local);
+        let mut buf = [0u8; 8];
+        let (n, from) = peer.recv_from(&mut buf).unwrap();
+        assert_eq!((&buf[..n], from), (&b"clone"[..], local));
+    }
+
+    #[test]
+    fn recv_flags_apply_to_a_single_call() {
+        use crate::socket::option::ReceiveTimeout;
+
+        let peer = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut socket = UdpSocket::new()
+            .unwrap()
+            .bind(Some(SocketAddr::from(([127, 0, 0, 1], 0))))
+            .unwrap();
+        let timeout = core::time::Duration::from_millis(100);
+        socket.set_option::<ReceiveTimeout>(Some(timeout)).unwrap();
+        let mut buf = [0u8; 8];
+
+        socket.set_recv_flags(SocketRecvFlags::MSG_DONTWAIT);
+        assert_eq!(socket.recv_from(&mut buf), Err(SocketError::WouldBlock));
+        peer.send_to(b"peek", socket.local_addr().unwrap()).unwrap();
+        socket.set_recv_flags(SocketRecvFlags::MSG_PEEK);
+        assert_eq!(socket.recv_from(&mut buf).unwrap().0, 4);
+
+        // the socket is still blocking: without flags, the receive timeout applies
+        socket.set_recv_flags(SocketRecvFlags::NONE);
+        assert_eq!(socket.recv_from(&mut buf).unwrap().0, 4);
+        assert_eq!(&buf[..4], b"peek");
+        assert!(!socket.option::<NonBlocking>().unwrap());
+        let start = std::time::Instant::now();
+        assert!(socket.recv_from(&mut buf).is_err());
+        assert!(start.elapsed() >= timeout);
+    }
+}
@@ -1,11 +1,11 @@
 #![allow(clippy::module_name_repetitions)]
 
-use core::fmt::Debug;
+use core::{fmt::Debug, time::Duration};
 
 use alloc::string::String;
 
+use crate::netc::in_addr;
 use core::net::SocketAddr;
-use psp::sys::in_addr;
 
 /// Trait for resolving hostnames
 ///
@@ -18,6 +18,31 @@ pub trait ResolveHostname {
     /// # Errors
     /// An error will be returned if the hostname could not be resolved.
     fn resolve_hostname(&mut self, hostname: &str) -> Result<SocketAddr, Self::Error>;
+
+    /// Resolve a hostname to an IP address, with the time the address can be
+    /// cached for
+    ///
+    /// The default implementation calls [`ResolveHostname::resolve_hostname`],
+    /// and returns no time: it is left to the caller.
+    ///
+    /// # Errors
+    /// An error will be returned if the hostname could not be resolved.
+    fn resolve_hostname_with_ttl(
+        &mut self,
+        hostname: &str,
+    ) -> Result<(SocketAddr, Option<Duration>), Self::Error> {
+        self.resolve_hostname(hostname).map(|addr| (addr, None))
+    }
+
+    /// Whether an error is an authoritative negative answer, such as a
+    /// hostname that does not exist, rather than a failure to get an answer
+    ///
+    /// Negative answers can be cached, by a `CachingResolver` for instance.
+    /// The default implementation returns `false`: no error is cached.
+    fn is_negative_answer(error: &Self::Error) -> bool {
+        let _ = error;
+        false
+    }
 }
 
 /// Trait for resolving IP addresses
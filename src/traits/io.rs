@@ -1,3 +1,7 @@
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::fmt::Debug;
+use thiserror::Error;
+
 pub trait OptionType {
     type Options<'b>: ?Sized;
 }
@@ -39,7 +43,366 @@ pub trait Open<'a, 'b>: ErrorType + OptionType {
 /// to close the acquired resources.
 pub trait EasySocket: Write + Read {}
 
+/// Capacity of the buffer of a [`BufReader`] created with [`BufReader::new`]
+pub const DEFAULT_BUF_READER_CAPACITY: usize = 1024;
+
+/// Error returned by [`BufReader::read_until`] and [`BufReader::read_line`]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReadLineError<E: Debug> {
+    /// The line is longer than the maximum line length
+    #[error("Line longer than {0} bytes")]
+    TooLong(usize),
+    /// The line is not valid UTF-8
+    #[error("Line is not valid UTF-8")]
+    InvalidUtf8,
+    /// The underlying reader failed
+    #[error("Read error: {0:?}")]
+    Read(E),
+}
+
+/// A buffered reader, adding line and delimiter based reads to any [`Read`]
+///
+/// It works over any [`EasySocket`], like a connected
+/// [`TcpSocket`](crate::socket::tcp::TcpSocket) or a ready
+/// [`TlsSocket`](crate::socket::tls::TlsSocket).
+///
+/// The internal buffer is a boxed slice by default. Any storage implementing
+/// [`AsRef<[u8]>`] and [`AsMut<[u8]>`] (e.g. a byte array) can be used instead,
+/// with [`BufReader::with_buffer`].
+///
+/// # Example
+/// ```ignore
+/// use psp_net::traits::io::BufReader;
+///
+/// let mut reader = BufReader::new(socket);
+/// reader.set_max_line_length(Some(8 * 1024));
+///
+/// let mut line = String::new();
+/// while reader.read_line(&mut line)? > 0 {
+///     if line == "\r\n" {
+///         break; // end of the HTTP headers
+///     }
+///     line.clear();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BufReader<R, B = Box<[u8]>> {
+    /// The underlying reader
+    inner: R,
+    /// The internal buffer
+    buffer: B,
+    /// Index of the first unconsumed byte of the buffer
+    pos: usize,
+    /// Number of bytes of the buffer filled by the underlying reader
+    filled: usize,
+    /// Maximum length of a line, delimiter included
+    max_line_length: Option<usize>,
+}
+
+impl<R: Read> BufReader<R> {
+    /// Create a buffered reader, with a buffer of [`DEFAULT_BUF_READER_CAPACITY`] bytes
+    ///
+    /// # Arguments
+    /// - `inner`: The underlying reader
+    #[must_use]
+    pub fn new(inner: R) -> Self {
+        Self::with_capacity(DEFAULT_BUF_READER_CAPACITY, inner)
+    }
+
+    /// Create a buffered reader, with a buffer of `capacity` bytes
+    ///
+    /// # Arguments
+    /// - `capacity`: The size of the internal buffer
+    /// - `inner`: The underlying reader
+    ///
+    /// # Panics
+    /// If `capacity` is `0`
+    #[must_use]
+    pub fn with_capacity(capacity: usize, inner: R) -> Self {
+        Self::with_buffer(inner, vec![0; capacity].into_boxed_slice())
+    }
+}
+
+impl<R: Read, B: AsRef<[u8]> + AsMut<[u8]>> BufReader<R, B> {
+    /// Create a buffered reader, buffering the data in `buffer`
+    ///
+    /// # Arguments
+    /// - `inner`: The underlying reader
+    /// - `buffer`: The internal buffer. Its content is overwritten.
+    ///
+    /// # Panics
+    /// If `buffer` is empty: nothing could be read into it, which would be
+    /// mistaken for the end of the stream.
+    #[must_use]
+    pub fn with_buffer(inner: R, buffer: B) -> Self {
+        assert!(
+            !buffer.as_ref().is_empty(),
+            "the buffer of a BufReader cannot be empty"
+        );
+        Self {
+            inner,
+            buffer,
+            pos: 0,
+            filled: 0,
+            max_line_length: None,
+        }
+    }
+
+    /// Get a reference to the underlying reader
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying reader
+    ///
+    /// Reading from it directly skips the data in the internal buffer.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying reader
+    ///
+    /// The data in the internal buffer is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// The size of the internal buffer
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.buffer.as_ref().len()
+    }
+
+    /// The data in the internal buffer, not consumed yet
+    #[must_use]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer.as_ref()[self.pos..self.filled]
+    }
+
+    /// Maximum length of a line read by [`read_until`](Self::read_until) and
+    /// [`read_line`](Self::read_line), delimiter included
+    #[must_use]
+    pub fn max_line_length(&self) -> Option<usize> {
+        self.max_line_length
+    }
+
+    /// Set the maximum length of a line, delimiter included
+    ///
+    /// This guards against peers sending endless lines. By default, lines
+    /// have no maximum length.
+    ///
+    /// # Arguments
+    /// - `max_line_length`: The maximum length, `None` for no maximum
+    pub fn set_max_line_length(&mut self, max_line_length: Option<usize>) {
+        self.max_line_length = max_line_length;
+    }
+
+    /// Read bytes into `buf` until `delimiter` or the end of the stream
+    ///
+    /// The delimiter, if found, is appended to `buf` as well.
+    ///
+    /// # Returns
+    /// The number of bytes appended to `buf`, `0` at the end of the stream.
+    ///
+    /// # Errors
+    /// - [`ReadLineError::TooLong`] if no delimiter was found within the maximum
+    ///   line length. The bytes read so far are appended to `buf`, and the rest
+    ///   of the line is left to read.
+    /// - [`ReadLineError::Read`] if the underlying reader failed
+    pub fn read_until(
+        &mut self,
+        delimiter: u8,
+        buf: &mut Vec<u8>,
+    ) -> Result<usize, ReadLineError<R::Error>> {
+        let max = self.max_line_length.unwrap_or(usize::MAX);
+        let mut read = 0;
+        loop {
+            let available = self.fill_buf().map_err(ReadLineError::Read)?;
+            if available.is_empty() {
+                return Ok(read);
+            }
+
+            let allowed = available.len().min(max - read);
+            let (len, done) = match available[..allowed].iter().position(|&b| b == delimiter) {
+                Some(i) => (i + 1, true),
+                None => (allowed, false),
+            };
+            buf.extend_from_slice(&available[..len]);
+            self.consume(len);
+            read += len;
+
+            if done {
+                return Ok(read);
+            }
+            if read == max {
+                return Err(ReadLineError::TooLong(max));
+            }
+        }
+    }
+
+    /// Read a line into `buf`
+    ///
+    /// The line ends with a `\n`, which is appended to `buf` as well (so is a
+    /// preceding `\r`), or at the end of the stream.
+    ///
+    /// # Returns
+    /// The number of bytes appended to `buf`, `0` at the end of the stream.
+    ///
+    /// # Errors
+    /// - [`ReadLineError::InvalidUtf8`] if the line is not valid UTF-8. `buf` is
+    ///   left unchanged, and the line is consumed.
+    /// - Same as [`read_until`](Self::read_until): the bytes read so far are
+    ///   appended to `buf`. If the error cuts a character in two, its first
+    ///   bytes are dropped.
+    pub fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadLineError<R::Error>> {
+        let mut line = Vec::new();
+        let result = self.read_until(b'\n', &mut line);
+        let line = match core::str::from_utf8(&line) {
+            Ok(line) => line,
+            // a line cut short may end in the middle of a character
+            Err(error) if result.is_err() && error.error_len().is_none() => {
+                core::str::from_utf8(&line[..error.valid_up_to()]).unwrap_or_default()
+            }
+            Err(_) => return Err(ReadLineError::InvalidUtf8),
+        };
+        buf.push_str(line);
+        result
+    }
+}
+
+impl<R: Read, B> embedded_io::ErrorType for BufReader<R, B> {
+    type Error = R::Error;
+}
+
+impl<R: Read, B: AsRef<[u8]> + AsMut<[u8]>> embedded_io::Read for BufReader<R, B> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        // large reads skip the internal buffer when it is empty
+        if self.pos == self.filled && buf.len() >= self.capacity() {
+            return self.inner.read(buf);
+        }
+
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: Read, B: AsRef<[u8]> + AsMut<[u8]>> embedded_io::BufRead for BufReader<R, B> {
+    fn fill_buf(&mut self) -> Result<&[u8], Self::Error> {
+        if self.pos == self.filled {
+            self.filled = self.inner.read(self.buffer.as_mut())?;
+            self.pos = 0;
+        }
+        Ok(self.buffer())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled);
+    }
+}
+
 // re-exports
 pub trait Write = embedded_io::Write;
 pub trait Read = embedded_io::Read;
 pub trait ErrorType = embedded_io::ErrorType;
+pub trait BufRead = embedded_io::BufRead;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader returning `data` a few bytes at a time
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl embedded_io::ErrorType for Chunked<'_> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl embedded_io::Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            let len = self.data.len().min(buf.len()).min(self.chunk);
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn read_lines_across_chunks() {
+        let data = Chunked {
+            data: b"HTTP/1.1 200 OK\r\nHost: psp\r\n\r\ntail",
+            chunk: 3,
+        };
+        let mut reader = BufReader::with_buffer(data, [0u8; 8]);
+
+        let mut line = String::new();
+        assert_eq!(reader.read_line(&mut line), Ok(17));
+        assert_eq!(line, "HTTP/1.1 200 OK\r\n");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "Host: psp\r\n");
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "\r\n");
+
+        let mut rest = [0u8; 4];
+        reader.read_exact(&mut rest).unwrap();
+        assert_eq!(&rest, b"tail");
+        assert_eq!(reader.read_line(&mut line), Ok(0));
+    }
+
+    #[test]
+    fn max_line_length_is_enforced() {
+        let mut reader = BufReader::new(&b"0123456789\nok\n"[..]);
+        reader.set_max_line_length(Some(4));
+
+        let mut buf = Vec::new();
+        assert_eq!(
+            reader.read_until(b'\n', &mut buf),
+            Err(ReadLineError::TooLong(4))
+        );
+        assert_eq!(buf, b"0123");
+        buf.clear();
+
+        reader.set_max_line_length(None);
+        reader.read_until(b'\n', &mut buf).unwrap();
+        assert_eq!(buf, b"456789\n");
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "ok\n");
+    }
+
+    #[test]
+    fn too_long_line_keeps_the_bytes_read() {
+        let mut reader = BufReader::new("0123ab€\n".as_bytes());
+        reader.set_max_line_length(Some(4));
+
+        let mut line = String::new();
+        assert_eq!(reader.read_line(&mut line), Err(ReadLineError::TooLong(4)));
+        assert_eq!(line, "0123");
+        // the limit cuts `€` in two
+        assert_eq!(reader.read_line(&mut line), Err(ReadLineError::TooLong(4)));
+        assert_eq!(line, "0123ab");
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot be empty")]
+    fn zero_capacity_panics() {
+        let _ = BufReader::with_capacity(0, &b""[..]);
+    }
+
+    #[test]
+    fn invalid_utf8_leaves_line_untouched() {
+        let mut reader = BufReader::new(&b"\xff\xfe\n"[..]);
+        let mut line = String::from("kept");
+        assert_eq!(reader.read_line(&mut line), Err(ReadLineError::InvalidUtf8));
+        assert_eq!(line, "kept");
+    }
+}
@@ -3,7 +3,7 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub mod dns;
 pub mod io;
 
@@ -18,6 +18,9 @@ pub trait SocketBuffer: Clone + Debug + Default {
 
     /// Append a buffer to the end.
     ///
+    /// Buffers with a bounded capacity only append the first
+    /// [`spare_capacity`](Self::spare_capacity) bytes of `buf`.
+    ///
     /// # Arguments
     /// - `buf`: buffer containing the data to be appended
     fn append_buffer(&mut self, buf: &[u8]);
@@ -39,12 +42,36 @@ pub trait SocketBuffer: Clone + Debug + Default {
 
     /// Get the buffer as a slice
     ///
+    /// Buffers that do not store their data contiguously (e.g. a ring buffer)
+    /// return the data at the front of the buffer, which may be shorter than
+    /// [`len`](Self::len). It is never empty if the buffer is not.
+    ///
     /// # Returns
     /// - The buffer as a slice of bytes
     fn as_slice(&self) -> &[u8];
 
+    /// Store the data contiguously, and get it as a slice
+    ///
+    /// Unlike [`as_slice`](Self::as_slice), the slice holds all the data of the
+    /// buffer. Buffers that always store their data contiguously, like the
+    /// default implementation, return [`as_slice`](Self::as_slice).
+    ///
+    /// # Returns
+    /// - The buffer as a slice of bytes, of length [`len`](Self::len)
+    fn make_contiguous(&mut self) -> &[u8] {
+        self.as_slice()
+    }
+
     /// Get the length of the buffer
     fn len(&self) -> usize;
+
+    /// Number of bytes that can still be appended
+    ///
+    /// Buffers that grow as needed, like the default implementation, return
+    /// [`usize::MAX`].
+    fn spare_capacity(&self) -> usize {
+        usize::MAX
+    }
 }
 
 impl SocketBuffer for Vec<u8> {
@@ -55,7 +82,7 @@ impl SocketBuffer for Vec<u8> {
 
     #[inline]
     fn append_buffer(&mut self, buf: &[u8]) {
-        self.append(&mut buf.to_vec());
+        self.extend_from_slice(buf);
     }
 
     fn shift_left_buffer(&mut self, amount: usize) {
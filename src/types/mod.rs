@@ -1,13 +1,16 @@
+mod ring_buffer;
 mod socket_flags;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 mod socket_options;
 
 // re-exports
 pub type Certificate<'a> = embedded_tls::Certificate<'a>;
 
+pub use ring_buffer::RingBuffer;
+pub use ring_buffer::DEFAULT_RING_BUFFER_CAPACITY;
 pub use socket_flags::SocketRecvFlags;
 pub use socket_flags::SocketSendFlags;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub use socket_options::SocketOptions;
-#[cfg(feature = "psp")]
+#[cfg(any(feature = "psp", feature = "host"))]
 pub use socket_options::TlsSocketOptions;
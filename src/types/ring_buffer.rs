@@ -0,0 +1,207 @@
+use alloc::{boxed::Box, vec};
+use core::fmt::{self, Debug};
+
+use crate::traits::SocketBuffer;
+
+/// Capacity of a [`RingBuffer`] created with [`RingBuffer::default`]
+pub const DEFAULT_RING_BUFFER_CAPACITY: usize = 16 * 1024;
+
+/// A fixed-capacity ring buffer
+///
+/// Unlike `Vec<u8>`, the buffer never grows, and removing sent data from its
+/// front does not move the remaining data. It is allocated once, when created.
+///
+/// # Notes
+/// - When the buffer is full, further data is not appended: sockets using it
+///   return short writes instead (see [`SocketBuffer::spare_capacity`]).
+/// - The data may wrap around the end of the storage, in which case
+///   [`as_slice`](SocketBuffer::as_slice) only returns its first part. Use
+///   [`as_slices`](Self::as_slices) to get all of it, or
+///   [`make_contiguous`](SocketBuffer::make_contiguous) to get it in one slice.
+#[derive(Clone)]
+pub struct RingBuffer {
+    /// The storage
+    data: Box<[u8]>,
+    /// Index of the first byte of data
+    head: usize,
+    /// Number of bytes of data
+    len: usize,
+}
+
+impl RingBuffer {
+    /// Create a new, empty, ring buffer
+    ///
+    /// # Arguments
+    /// - `capacity`: The maximum number of bytes the buffer holds
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: vec![0; capacity].into_boxed_slice(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// The maximum number of bytes the buffer holds
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the buffer holds as many bytes as its capacity
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    /// Get the data, as two slices
+    ///
+    /// # Returns
+    /// The data at the front of the buffer, and the data that wrapped around
+    /// the end of the storage (empty if it did not).
+    #[must_use]
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let first_len = self.len.min(self.capacity() - self.head);
+        (
+            &self.data[self.head..self.head + first_len],
+            &self.data[..self.len - first_len],
+        )
+    }
+}
+
+impl Default for RingBuffer {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_RING_BUFFER_CAPACITY)
+    }
+}
+
+impl Debug for RingBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SocketBuffer for RingBuffer {
+    #[inline]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a buffer to the end
+    ///
+    /// Only the first [`spare_capacity`](SocketBuffer::spare_capacity) bytes
+    /// of `buf` are appended.
+    fn append_buffer(&mut self, buf: &[u8]) {
+        let amount = buf.len().min(self.spare_capacity());
+        if amount == 0 {
+            return;
+        }
+
+        let tail = (self.head + self.len) % self.capacity();
+        let first_len = amount.min(self.capacity() - tail);
+        self.data[tail..tail + first_len].copy_from_slice(&buf[..first_len]);
+        self.data[..amount - first_len].copy_from_slice(&buf[first_len..amount]);
+        self.len += amount;
+    }
+
+    fn shift_left_buffer(&mut self, amount: usize) {
+        let amount = amount.min(self.len);
+        if amount == 0 {
+            return;
+        }
+
+        self.len -= amount;
+        // keep the data contiguous for as long as possible
+        self.head = if self.len == 0 {
+            0
+        } else {
+            (self.head + amount) % self.capacity()
+        };
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    #[inline]
+    fn as_slice(&self) -> &[u8] {
+        self.as_slices().0
+    }
+
+    /// Store the data contiguously, and get it as a slice
+    ///
+    /// The data is moved to the start of the storage if it wrapped around its
+    /// end.
+    fn make_contiguous(&mut self) -> &[u8] {
+        if self.head + self.len > self.capacity() {
+            self.data.rotate_left(self.head);
+            self.head = 0;
+        }
+        &self.data[self.head..self.head + self.len]
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn spare_capacity(&self) -> usize {
+        self.capacity() - self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_around_and_stays_bounded() {
+        let mut buffer = RingBuffer::with_capacity(8);
+        buffer.append_buffer(b"abcdef");
+        buffer.shift_left_buffer(4);
+        assert_eq!(buffer.as_slice(), b"ef");
+
+        buffer.append_buffer(b"ghijklmnop");
+        assert!(buffer.is_full());
+        assert_eq!(buffer.as_slices(), (&b"efgh"[..], &b"ijkl"[..]));
+        assert_eq!(buffer.as_slice(), b"efgh");
+
+        buffer.shift_left_buffer(4);
+        assert_eq!(buffer.as_slice(), b"ijkl");
+        assert_eq!(buffer.spare_capacity(), 4);
+
+        buffer.shift_left_buffer(100);
+        assert!(buffer.is_empty());
+        buffer.append_buffer(b"12345678");
+        assert_eq!(buffer.as_slice(), b"12345678");
+    }
+
+    #[test]
+    fn make_contiguous_joins_wrapped_data() {
+        let mut buffer = RingBuffer::with_capacity(8);
+        buffer.append_buffer(b"abcdef");
+        buffer.shift_left_buffer(4);
+        buffer.append_buffer(b"ghij");
+        assert_eq!(buffer.as_slices(), (&b"efgh"[..], &b"ij"[..]));
+
+        assert_eq!(buffer.make_contiguous(), b"efghij");
+        assert_eq!(buffer.as_slice(), b"efghij");
+        buffer.append_buffer(b"kl");
+        assert_eq!(buffer.as_slices(), (&b"efghijkl"[..], &b""[..]));
+    }
+
+    #[test]
+    fn zero_capacity_holds_nothing() {
+        let mut buffer = RingBuffer::with_capacity(0);
+        buffer.append_buffer(b"data");
+        buffer.shift_left_buffer(1);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_slice(), b"");
+    }
+}
@@ -10,6 +10,8 @@ bitflags! {
         const MSG_OOB = 0x1;
         /// End of record
         const MSG_EOR = 0x8;
+        /// Do not block, even if the socket is in blocking mode
+        const MSG_DONTWAIT = 0x80;
     }
 }
 
@@ -33,6 +35,8 @@ bitflags! {
         const MSG_PEEK = 0x2;
         /// Wait for full message
         const MSG_WAITALL = 0x40;
+        /// Do not block, even if the socket is in blocking mode
+        const MSG_DONTWAIT = 0x80;
     }
 }
 
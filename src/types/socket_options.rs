@@ -1,8 +1,9 @@
 #![allow(clippy::module_name_repetitions)]
 
 use alloc::string::String;
-use psp::sys;
+use core::time::Duration;
 
+use crate::backend::{DefaultBackend, NetBackend};
 use crate::socket::SocketAddr;
 
 use super::Certificate;
@@ -15,17 +16,25 @@ use super::Certificate;
 ///
 /// # Fields
 /// - [`remote`](Self::remote): Remote address to connect to
+/// - [`connect_timeout`](Self::connect_timeout): Maximum time to wait for a TCP connection
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct SocketOptions {
     /// Remote address to connect to
     remote: SocketAddr,
+    /// Maximum time to wait for a TCP connection to be established
+    connect_timeout: Option<Duration>,
 }
 
 impl SocketOptions {
     /// Create a new socket options
+    ///
+    /// By default, no connect timeout is set.
     #[must_use]
     pub fn new(remote: SocketAddr) -> SocketOptions {
-        Self { remote }
+        Self {
+            remote,
+            connect_timeout: None,
+        }
     }
 
     /// Get the remote address
@@ -33,6 +42,24 @@ impl SocketOptions {
     pub fn remote(&self) -> SocketAddr {
         self.remote
     }
+
+    /// Get the connect timeout
+    #[must_use]
+    pub fn connect_timeout(&self) -> Option<Duration> {
+        self.connect_timeout
+    }
+
+    /// Set the connect timeout
+    ///
+    /// When set, opening a [`TcpSocket`](super::socket::tcp::TcpSocket) fails
+    /// if the connection is not established within the timeout.
+    /// UDP sockets ignore it.
+    ///
+    /// # Arguments
+    /// - `connect_timeout`: The connect timeout, `None` to wait indefinitely
+    pub fn set_connect_timeout(&mut self, connect_timeout: Option<Duration>) {
+        self.connect_timeout = connect_timeout;
+    }
 }
 
 /// TLS socket options.
@@ -92,17 +119,13 @@ impl<'a> TlsSocketOptions<'a> {
     ///
     /// # Notes
     /// Like [`TlsSocketOptions::new`], but the seed is based on the current time.
-    /// Uses [`sys::sceRtcGetCurrentTick`] to get the current time.
+    /// Uses [`NetBackend::now_micros`] to get the current time.
     #[must_use]
     pub fn new_with_seed_from_time<S>(server_name: S) -> Self
     where
         S: Into<String>,
     {
-        let seed = unsafe {
-            let mut seed: u64 = 0;
-            sys::sceRtcGetCurrentTick(&mut seed);
-            seed
-        };
+        let seed = DefaultBackend::now_micros();
 
         Self::new(seed, server_name)
     }
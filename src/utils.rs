@@ -1,6 +1,8 @@
 use alloc::{borrow::ToOwned, string::String};
 use thiserror::Error;
 
+use crate::backend::{DefaultBackend, NetBackend};
+
 /// Error type for net functions
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[must_use]
@@ -43,29 +45,7 @@ impl NetError {
 #[allow(unused)]
 #[inline]
 pub fn load_net_modules() -> Result<(), NetError> {
-    unsafe {
-        let res = psp::sys::sceUtilityLoadNetModule(psp::sys::NetModule::NetCommon);
-        if res != 0 {
-            return Err(NetError::load_module_failed("", res));
-        }
-
-        let res = psp::sys::sceUtilityLoadNetModule(psp::sys::NetModule::NetInet);
-        if res != 0 {
-            return Err(NetError::load_module_failed("", res));
-        }
-
-        let res = psp::sys::sceUtilityLoadNetModule(psp::sys::NetModule::NetParseUri);
-        if res != 0 {
-            return Err(NetError::load_module_failed("", res));
-        }
-
-        let res = psp::sys::sceUtilityLoadNetModule(psp::sys::NetModule::NetHttp);
-        if res != 0 {
-            return Err(NetError::load_module_failed("", res));
-        }
-
-        Ok(())
-    }
+    DefaultBackend::load_net_modules()
 }
 
 /// Initialize network
@@ -75,29 +55,7 @@ pub fn load_net_modules() -> Result<(), NetError> {
 #[allow(unused)]
 #[inline]
 pub fn net_init() -> Result<(), NetError> {
-    unsafe {
-        let res = psp::sys::sceNetInit(0x20000, 0x20, 0x1000, 0x20, 0x1000);
-        if res != 0 {
-            return Err(NetError::init_failed("sceNetInit", res));
-        }
-
-        let res = psp::sys::sceNetInetInit();
-        if res != 0 {
-            return Err(NetError::init_failed("sceNetInetInit", res));
-        }
-
-        let res = psp::sys::sceNetResolverInit();
-        if res != 0 {
-            return Err(NetError::init_failed("sceNetResolverInit", res));
-        }
-
-        let res = psp::sys::sceNetApctlInit(0x1600, 42);
-        if res != 0 {
-            return Err(NetError::init_failed("sceNetApctlInit", res));
-        }
-    }
-
-    Ok(())
+    DefaultBackend::net_init()
 }
 
 /// Check existence of net config
@@ -113,11 +71,8 @@ pub fn net_init() -> Result<(), NetError> {
 #[allow(unused)]
 #[inline]
 pub fn check_netconfig_existence(id: i32) -> Result<(), NetError> {
-    unsafe {
-        let res = psp::sys::sceUtilityCheckNetParam(id);
-        if res != 0 {
-            return Err(NetError::NetConfigNotExist);
-        }
+    if DefaultBackend::check_netconfig(id) != 0 {
+        return Err(NetError::NetConfigNotExist);
     }
 
     Ok(())
@@ -149,11 +104,9 @@ pub fn check_first_netconfig_existence() -> Result<(), NetError> {
 /// Use [`block_until_connected`] right after
 /// this function to block until the connection is established.
 pub fn init_connection_to_access_point(connection_id: i32) -> Result<(), NetError> {
-    unsafe {
-        let res = psp::sys::sceNetApctlConnect(connection_id);
-        if res != 0 {
-            return Err(NetError::error("sceNetApctlConnect", res));
-        }
+    let res = DefaultBackend::connect_to_access_point(connection_id);
+    if res != 0 {
+        return Err(NetError::error("sceNetApctlConnect", res));
     }
 
     Ok(())
@@ -163,7 +116,7 @@ pub fn init_connection_to_access_point(connection_id: i32) -> Result<(), NetErro
 ///
 /// Polls the access point state until connection is established, `desist_after` times
 /// is reached, or an error occurs.
-/// The polling is done at 50ms intervals using [`sceKernelDelayThread`](psp::sys::sceKernelDelayThread).
+/// The polling is done at 50ms intervals using [`NetBackend::delay`].
 ///
 /// # Parameters
 /// - `desist_after`: The number of times to poll the state before desisting
@@ -172,18 +125,13 @@ pub fn init_connection_to_access_point(connection_id: i32) -> Result<(), NetErro
 /// Same as [`init_connection_to_access_point`]
 #[inline]
 pub fn block_until_connected(desist_after: usize) -> Result<(), NetError> {
-    let mut state: psp::sys::ApctlState = unsafe { core::mem::zeroed() };
     for _ in 0..desist_after {
-        unsafe {
-            let err = psp::sys::sceNetApctlGetState(&mut state);
-            if err != 0 {
-                return Err(NetError::error("sceNetApctlGetState", err));
-            }
-        }
-        if let psp::sys::ApctlState::GotIp = state {
-            return Ok(());
+        match DefaultBackend::access_point_connected() {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(err) => return Err(NetError::error("sceNetApctlGetState", err)),
         }
-        unsafe { psp::sys::sceKernelDelayThread(50_000) };
+        DefaultBackend::delay(50_000);
     }
 
     Err(NetError::Timeout)